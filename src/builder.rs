@@ -0,0 +1,158 @@
+use crate::{MutableValue, ParsedDocument};
+
+/// Builds a [`ParsedDocument`] field by field with a chaining API.
+///
+/// ```
+/// use mutable_bson::MutableDocumentBuilder;
+///
+/// let doc = MutableDocumentBuilder::new()
+///     .field("name", "widget")
+///     .nested("dims", |b| {
+///         b.field("w", 1.5).field("h", 2.0);
+///     })
+///     .array("tags", |a| {
+///         a.push("a").push("b");
+///     })
+///     .build();
+/// assert_eq!(doc.get_str("name"), Some("widget"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MutableDocumentBuilder {
+    doc: ParsedDocument<'static>,
+}
+
+impl MutableDocumentBuilder {
+    /// Create a builder for an empty document.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field with value, replacing any existing field with the same key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if key contains a null byte, which cannot be encoded in BSON.
+    pub fn field<V: Into<MutableValue<'static>>>(
+        &mut self,
+        key: impl Into<String>,
+        value: V,
+    ) -> &mut Self {
+        let key = key.into();
+        assert!(!key.contains('\0'), "key {:?} contains a null byte", key);
+        self.doc.insert(key, value);
+        self
+    }
+
+    /// Add a nested document built by f.
+    ///
+    /// # Panics
+    ///
+    /// Panics if key contains a null byte, which cannot be encoded in BSON.
+    pub fn nested(
+        &mut self,
+        key: impl Into<String>,
+        f: impl FnOnce(&mut MutableDocumentBuilder),
+    ) -> &mut Self {
+        let mut builder = MutableDocumentBuilder::new();
+        f(&mut builder);
+        self.field(key, builder.build())
+    }
+
+    /// Add an array built by f.
+    ///
+    /// # Panics
+    ///
+    /// Panics if key contains a null byte, which cannot be encoded in BSON.
+    pub fn array(
+        &mut self,
+        key: impl Into<String>,
+        f: impl FnOnce(&mut MutableArrayBuilder),
+    ) -> &mut Self {
+        let mut builder = MutableArrayBuilder::default();
+        f(&mut builder);
+        self.field(key, builder.values)
+    }
+
+    /// Return the built document, leaving the builder empty.
+    pub fn build(&mut self) -> ParsedDocument<'static> {
+        std::mem::take(&mut self.doc)
+    }
+}
+
+/// Builds an array for [`MutableDocumentBuilder::array`].
+#[derive(Clone, Debug, Default)]
+pub struct MutableArrayBuilder {
+    values: Vec<MutableValue<'static>>,
+}
+
+impl MutableArrayBuilder {
+    /// Append value to the array.
+    pub fn push<V: Into<MutableValue<'static>>>(&mut self, value: V) -> &mut Self {
+        self.values.push(value.into());
+        self
+    }
+
+    /// Append a document built by f.
+    pub fn nested(&mut self, f: impl FnOnce(&mut MutableDocumentBuilder)) -> &mut Self {
+        let mut builder = MutableDocumentBuilder::new();
+        f(&mut builder);
+        self.push(builder.build())
+    }
+
+    /// Append an array built by f.
+    pub fn array(&mut self, f: impl FnOnce(&mut MutableArrayBuilder)) -> &mut Self {
+        let mut builder = MutableArrayBuilder::default();
+        f(&mut builder);
+        self.push(builder.values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use super::MutableDocumentBuilder;
+
+    #[test]
+    fn build() {
+        let doc = MutableDocumentBuilder::new()
+            .field("a", 1)
+            .nested("b", |b| {
+                b.field("c", "d").nested("e", |_| {});
+            })
+            .array("f", |a| {
+                a.push(1)
+                    .nested(|b| {
+                        b.field("g", true);
+                    })
+                    .array(|a| {
+                        a.push("h");
+                    });
+            })
+            .field("a", 2)
+            .build();
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! {
+                "a": 2,
+                "b": { "c": "d", "e": {} },
+                "f": [1, { "g": true }, ["h"]],
+            }
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn build_resets() {
+        let mut builder = MutableDocumentBuilder::new();
+        builder.field("a", 1);
+        assert_eq!(builder.build().len(), 1);
+        assert!(builder.build().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "contains a null byte")]
+    fn null_key() {
+        MutableDocumentBuilder::new().field("a\0b", 1);
+    }
+}
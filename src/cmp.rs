@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+
+use bson::Decimal128;
+
+use crate::{MutableArray, MutableDocument, MutableValue, ParsedDocument};
+
+/// Returns the rank of the value's type in the MongoDB canonical sort order. Values of different
+/// numeric types share a rank and are compared numerically, as are strings and symbols.
+fn canonical_rank(value: &MutableValue<'_>) -> u8 {
+    match value {
+        MutableValue::MinKey => 0,
+        MutableValue::Undefined => 1,
+        MutableValue::Null => 2,
+        MutableValue::Double(_)
+        | MutableValue::Int32(_)
+        | MutableValue::Int64(_)
+        | MutableValue::Decimal128(_) => 3,
+        MutableValue::String(_) | MutableValue::Symbol(_) => 4,
+        MutableValue::Document(_) => 5,
+        MutableValue::Array(_) => 6,
+        MutableValue::Binary(_) => 7,
+        MutableValue::ObjectId(_) => 8,
+        MutableValue::Boolean(_) => 9,
+        MutableValue::DateTime(_) => 10,
+        MutableValue::Timestamp(_) => 11,
+        MutableValue::RegularExpression(_) => 12,
+        MutableValue::DbPointer(_) => 13,
+        MutableValue::JavaScriptCode(_) => 14,
+        MutableValue::JavaScriptCodeWithScope(_) => 15,
+        MutableValue::MaxKey => 16,
+    }
+}
+
+/// Compare two values using the MongoDB canonical BSON sort order.
+///
+/// Values are ordered first by type (`MinKey`, `Undefined`, `Null`, numbers, strings and symbols,
+/// documents, arrays, binary, `ObjectId`, booleans, dates, timestamps, regular expressions,
+/// db pointers, code, code with scope, `MaxKey`), then by value. Numbers of different types
+/// compare numerically with `NaN` ordered before all other numbers; comparisons involving a
+/// `Double` or `Decimal128` are made in `f64` precision. Documents compare field by field by
+/// type, key, then value, and arrays compare element by element. Malformed encoded documents and
+/// arrays compare as if they were empty.
+///
+/// This is suitable for use with [`MutableArray::sort_by()`].
+pub fn compare_values(a: &MutableValue<'_>, b: &MutableValue<'_>) -> Ordering {
+    canonical_rank(a)
+        .cmp(&canonical_rank(b))
+        .then_with(|| match (a, b) {
+            (MutableValue::Int32(a), MutableValue::Int32(b)) => a.cmp(b),
+            (MutableValue::Int32(a), MutableValue::Int64(b)) => i64::from(*a).cmp(b),
+            (MutableValue::Int64(a), MutableValue::Int32(b)) => a.cmp(&i64::from(*b)),
+            (MutableValue::Int64(a), MutableValue::Int64(b)) => a.cmp(b),
+            (
+                MutableValue::Double(_)
+                | MutableValue::Int32(_)
+                | MutableValue::Int64(_)
+                | MutableValue::Decimal128(_),
+                _,
+            ) => compare_f64(number_f64(a), number_f64(b)),
+            (
+                MutableValue::String(a) | MutableValue::Symbol(a),
+                MutableValue::String(b) | MutableValue::Symbol(b),
+            ) => a.cmp(b),
+            (MutableValue::Document(a), MutableValue::Document(b)) => compare_documents(a, b),
+            (MutableValue::Array(a), MutableValue::Array(b)) => compare_arrays(a, b),
+            (MutableValue::Binary(a), MutableValue::Binary(b)) => {
+                let (a_bytes, a_subtype) = a.parts();
+                let (b_bytes, b_subtype) = b.parts();
+                a_bytes
+                    .len()
+                    .cmp(&b_bytes.len())
+                    .then_with(|| u8::from(a_subtype).cmp(&u8::from(b_subtype)))
+                    .then_with(|| a_bytes.cmp(b_bytes))
+            }
+            (MutableValue::ObjectId(a), MutableValue::ObjectId(b)) => a.cmp(b),
+            (MutableValue::Boolean(a), MutableValue::Boolean(b)) => a.cmp(b),
+            (MutableValue::DateTime(a), MutableValue::DateTime(b)) => a.cmp(b),
+            (MutableValue::Timestamp(a), MutableValue::Timestamp(b)) => {
+                (a.time, a.increment).cmp(&(b.time, b.increment))
+            }
+            (MutableValue::RegularExpression(a), MutableValue::RegularExpression(b)) => {
+                a.parts().cmp(&b.parts())
+            }
+            (MutableValue::DbPointer(a), MutableValue::DbPointer(b)) => a.encode().cmp(&b.encode()),
+            (MutableValue::JavaScriptCode(a), MutableValue::JavaScriptCode(b)) => a.cmp(b),
+            (
+                MutableValue::JavaScriptCodeWithScope(a),
+                MutableValue::JavaScriptCodeWithScope(b),
+            ) => a.code().cmp(b.code()).then_with(|| {
+                let scope = |v: &crate::MutableJavaScriptCodeWithScope<'_>| {
+                    v.encoded_scope()
+                        .map(|s| s.into_owned())
+                        .unwrap_or_default()
+                };
+                scope(a).cmp(&scope(b))
+            }),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Orders values with [`compare_values()`].
+///
+/// Values of different types may compare as equal in the canonical order, like `Int32(1)` and
+/// `Double(1.0)`, but are not equal with [`PartialEq`]. These values are incomparable and
+/// `partial_cmp()` returns `None` to stay consistent with `==`.
+impl PartialOrd for MutableValue<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match compare_values(self, other) {
+            Ordering::Equal if self != other => None,
+            ord => Some(ord),
+        }
+    }
+}
+
+fn number_f64(value: &MutableValue<'_>) -> f64 {
+    match value {
+        MutableValue::Decimal128(d) => decimal128_to_f64(d),
+        v => v.as_number_f64().unwrap_or(f64::NAN),
+    }
+}
+
+fn decimal128_to_f64(d: &Decimal128) -> f64 {
+    d.to_string().parse().unwrap_or(f64::NAN)
+}
+
+/// Compare doubles ordering NaN before all other values, and treating all NaNs as equal.
+fn compare_f64(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.partial_cmp(&b).expect("not NaN"),
+    }
+}
+
+fn compare_documents(a: &MutableDocument<'_>, b: &MutableDocument<'_>) -> Ordering {
+    let a = a.as_parsed().unwrap_or_default();
+    let b = b.as_parsed().unwrap_or_default();
+    compare_parsed_documents(&a, &b)
+}
+
+fn compare_parsed_documents(a: &ParsedDocument<'_>, b: &ParsedDocument<'_>) -> Ordering {
+    for ((a_key, a_value), (b_key, b_value)) in a.iter().zip(b.iter()) {
+        let ord = canonical_rank(a_value)
+            .cmp(&canonical_rank(b_value))
+            .then_with(|| a_key.cmp(b_key))
+            .then_with(|| compare_values(a_value, b_value));
+        if ord.is_ne() {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn compare_arrays(a: &MutableArray<'_>, b: &MutableArray<'_>) -> Ordering {
+    let a = a.as_parsed().unwrap_or_default();
+    let b = b.as_parsed().unwrap_or_default();
+    for (a_value, b_value) in a.iter().zip(b.iter()) {
+        let ord = compare_values(a_value, b_value);
+        if ord.is_ne() {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+impl ParsedDocument<'_> {
+    /// Returns true if both documents contain the same keys in the same order with equal values.
+    ///
+    /// Unlike `==`, nested encoded documents and arrays are compared to parsed ones by decoding
+    /// them one value at a time rather than encoding the parsed value, so this only allocates
+    /// when decoding encoded documents. Two encoded values are compared by their bytes, so
+    /// identical malformed encodings are equal; otherwise malformed encoded values are never equal.
+    pub fn deep_equals(&self, other: &ParsedDocument<'_>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|((a_key, a_value), (b_key, b_value))| {
+                    a_key == b_key && values_deep_equal(a_value, b_value)
+                })
+    }
+}
+
+fn values_deep_equal(a: &MutableValue<'_>, b: &MutableValue<'_>) -> bool {
+    match (a, b) {
+        (MutableValue::Document(a), MutableValue::Document(b)) => documents_deep_equal(a, b),
+        (MutableValue::Array(a), MutableValue::Array(b)) => arrays_deep_equal(a, b),
+        (a, b) => a == b,
+    }
+}
+
+fn documents_deep_equal(a: &MutableDocument<'_>, b: &MutableDocument<'_>) -> bool {
+    match (a, b) {
+        (MutableDocument::Borrowed(a), MutableDocument::Borrowed(b)) => {
+            a.as_bytes() == b.as_bytes()
+        }
+        (MutableDocument::Owned(a), MutableDocument::Owned(b)) => a.deep_equals(b),
+        (a, b) => {
+            let (mut a, mut b) = (a.iter(), b.iter());
+            loop {
+                match (a.next(), b.next()) {
+                    (None, None) => return true,
+                    (Some(Ok((a_key, a_value))), Some(Ok((b_key, b_value))))
+                        if a_key == b_key && values_deep_equal(&a_value, &b_value) => {}
+                    _ => return false,
+                }
+            }
+        }
+    }
+}
+
+fn arrays_deep_equal(a: &MutableArray<'_>, b: &MutableArray<'_>) -> bool {
+    match (a, b) {
+        (MutableArray::Borrowed(a), MutableArray::Borrowed(b)) => a.as_bytes() == b.as_bytes(),
+        (a, b) => {
+            let (mut a, mut b) = (a.iter(), b.iter());
+            loop {
+                match (a.next(), b.next()) {
+                    (None, None) => return true,
+                    (Some(Ok(a_value)), Some(Ok(b_value)))
+                        if values_deep_equal(&a_value, &b_value) => {}
+                    _ => return false,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use bson::{Binary, DateTime, Decimal128, oid::ObjectId, rawdoc, spec::BinarySubtype};
+
+    use crate::{MutableArray, MutableDocument, MutableValue, ParsedDocument};
+
+    use super::compare_values;
+
+    #[test]
+    fn type_order() {
+        let raw = rawdoc! { "a": 1 };
+        let values: Vec<MutableValue<'_>> = vec![
+            MutableValue::MinKey,
+            MutableValue::Null,
+            MutableValue::Int32(1),
+            "a".into(),
+            MutableValue::Document(MutableDocument::from(raw.as_ref())),
+            Vec::<MutableValue>::new().into(),
+            Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: vec![],
+            }
+            .into(),
+            ObjectId::from_bytes([0; 12]).into(),
+            false.into(),
+            DateTime::from_millis(0).into(),
+            MutableValue::MaxKey,
+        ];
+        for (i, a) in values.iter().enumerate() {
+            for (j, b) in values.iter().enumerate() {
+                assert_eq!(compare_values(a, b), i.cmp(&j), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            compare_values(&MutableValue::Int32(2), &MutableValue::Int64(1)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_values(&MutableValue::Int64(2), &MutableValue::Double(2.0)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_values(
+                &MutableValue::Double(f64::NAN),
+                &MutableValue::Int32(i32::MIN)
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_values(
+                &MutableValue::Decimal128("1.5".parse::<Decimal128>().unwrap()),
+                &MutableValue::Int32(1)
+            ),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn documents_and_arrays() {
+        let raw = rawdoc! { "a": 1, "b": "x" };
+        let borrowed = MutableValue::Document(raw.as_ref().into());
+        let mut parsed = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(
+            compare_values(&borrowed, &parsed.clone().into()),
+            Ordering::Equal
+        );
+        parsed.insert("c", 1);
+        assert_eq!(
+            compare_values(&borrowed, &parsed.clone().into()),
+            Ordering::Less
+        );
+        parsed.insert("b", 1);
+        // Numbers sort before strings.
+        assert_eq!(compare_values(&borrowed, &parsed.into()), Ordering::Greater);
+
+        let a = MutableValue::Array(MutableArray::from(vec![
+            MutableValue::Int32(1),
+            MutableValue::Int32(2),
+        ]));
+        let b = MutableValue::Array(MutableArray::from(vec![MutableValue::Int32(3)]));
+        assert_eq!(compare_values(&a, &b), Ordering::Less);
+    }
+
+    #[test]
+    fn partial_ord() {
+        assert!(MutableValue::MinKey < MutableValue::Null);
+        assert!(MutableValue::Int32(5) < MutableValue::from("a"));
+        assert!(MutableValue::Int64(2) > MutableValue::Double(1.5));
+        assert!(MutableValue::MaxKey > MutableValue::Boolean(true));
+        assert_eq!(
+            MutableValue::from("a").partial_cmp(&MutableValue::from("a")),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            MutableValue::Int32(1).partial_cmp(&MutableValue::Double(1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn deep_equals() {
+        let raw = rawdoc! { "a": { "b": [1, { "c": "d" }] }, "e": 2.5 };
+        let borrowed = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        let parsed = parsed_doc! { "a": { "b": [1, { "c": "d" }] }, "e": 2.5 };
+        assert!(borrowed.deep_equals(&parsed));
+        assert!(parsed.deep_equals(&borrowed));
+        assert!(borrowed.deep_equals(&borrowed));
+
+        let different = parsed_doc! { "a": { "b": [1, { "c": "x" }] }, "e": 2.5 };
+        assert!(!borrowed.deep_equals(&different));
+        let shorter = parsed_doc! { "a": { "b": [1] }, "e": 2.5 };
+        assert!(!borrowed.deep_equals(&shorter));
+        let reordered = parsed_doc! { "e": 2.5, "a": { "b": [1, { "c": "d" }] } };
+        assert!(!borrowed.deep_equals(&reordered));
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+use bson::oid::ObjectId;
+
+use crate::{
+    MutableArray, MutableDbPointer, MutableDocument, MutableJavaScriptCodeWithScope, MutableValue,
+    ParsedDocument,
+};
+
+/// Formats the value like the MongoDB shell, where BSON-specific types are rendered as
+/// constructor calls like `ObjectId("...")`, `ISODate("...")` and `NumberLong(...)`.
+///
+/// This is intended for debug output and logging; encoded documents and arrays are truncated at
+/// the first malformed element.
+impl Display for MutableValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Double(v) if v.is_infinite() => {
+                f.write_str(if *v > 0.0 { "Infinity" } else { "-Infinity" })
+            }
+            Self::Double(v) => write!(f, "{}", v),
+            Self::String(v) | Self::Symbol(v) => write_quoted(f, v),
+            Self::Document(v) => write_fields(f, v.iter().map_while(Result::ok)),
+            Self::Array(v) => write_array(f, v),
+            Self::Binary(v) => {
+                let (bytes, subtype) = v.parts();
+                write!(f, "BinData({}, \"", u8::from(subtype))?;
+                write_base64(f, bytes)?;
+                f.write_str("\")")
+            }
+            Self::Undefined => f.write_str("undefined"),
+            Self::ObjectId(v) => write!(f, "ObjectId(\"{}\")", v),
+            Self::Boolean(v) => write!(f, "{}", v),
+            Self::DateTime(v) => match v.try_to_rfc3339_string() {
+                Ok(s) => write!(f, "ISODate(\"{}\")", s),
+                Err(_) => write!(f, "new Date({})", v.timestamp_millis()),
+            },
+            Self::Null => f.write_str("null"),
+            Self::RegularExpression(v) => {
+                let (pattern, options) = v.parts();
+                write!(f, "/{}/{}", pattern, options)
+            }
+            Self::DbPointer(v) => {
+                let (namespace, id) = db_pointer_parts(v);
+                f.write_str("DBPointer(")?;
+                write_quoted(f, &namespace)?;
+                write!(f, ", ObjectId(\"{}\"))", id)
+            }
+            Self::JavaScriptCode(v) => {
+                f.write_str("Code(")?;
+                write_quoted(f, v)?;
+                f.write_str(")")
+            }
+            Self::JavaScriptCodeWithScope(v) => write_code_with_scope(f, v),
+            Self::Int32(v) => write!(f, "{}", v),
+            Self::Timestamp(v) => write!(f, "Timestamp({{ t: {}, i: {} }})", v.time, v.increment),
+            Self::Int64(v) => write!(f, "NumberLong({})", v),
+            Self::Decimal128(v) => write!(f, "NumberDecimal(\"{}\")", v),
+            Self::MinKey => f.write_str("MinKey()"),
+            Self::MaxKey => f.write_str("MaxKey()"),
+        }
+    }
+}
+
+/// Formats the document like the MongoDB shell as `{ key: value, ... }`.
+///
+/// See the [`Display`] implementation for [`MutableValue`].
+impl Display for ParsedDocument<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_fields(
+            f,
+            self.iter().map(|(k, v)| (k, std::borrow::Cow::Borrowed(v))),
+        )
+    }
+}
+
+fn write_fields<'v, 'a: 'v>(
+    f: &mut Formatter<'_>,
+    fields: impl Iterator<Item = (&'v str, std::borrow::Cow<'v, MutableValue<'a>>)>,
+) -> fmt::Result {
+    let mut empty = true;
+    for (i, (k, v)) in fields.enumerate() {
+        f.write_str(if i == 0 { "{ " } else { ", " })?;
+        if is_identifier(k) {
+            f.write_str(k)?;
+        } else {
+            write_quoted(f, k)?;
+        }
+        write!(f, ": {}", v)?;
+        empty = false;
+    }
+    f.write_str(if empty { "{}" } else { " }" })
+}
+
+fn write_array(f: &mut Formatter<'_>, array: &MutableArray<'_>) -> fmt::Result {
+    let mut empty = true;
+    for (i, v) in array.iter().map_while(Result::ok).enumerate() {
+        f.write_str(if i == 0 { "[ " } else { ", " })?;
+        write!(f, "{}", v)?;
+        empty = false;
+    }
+    f.write_str(if empty { "[]" } else { " ]" })
+}
+
+fn write_code_with_scope(
+    f: &mut Formatter<'_>,
+    value: &MutableJavaScriptCodeWithScope<'_>,
+) -> fmt::Result {
+    f.write_str("Code(")?;
+    write_quoted(f, value.code())?;
+    f.write_str(", ")?;
+    let scope = value.encoded_scope().ok();
+    match scope.as_deref().map(bson::RawDocument::from_bytes) {
+        Some(Ok(scope)) => write!(
+            f,
+            "{}",
+            MutableValue::Document(MutableDocument::Borrowed(scope))
+        )?,
+        _ => f.write_str("{}")?,
+    }
+    f.write_str(")")
+}
+
+/// Returns true if key may be written without quotes in the shell.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Write s as a double quoted string with JSON escapes.
+fn write_quoted(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+fn write_base64(f: &mut Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                f.write_char(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char)?;
+            } else {
+                f.write_char('=')?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn db_pointer_parts(value: &MutableDbPointer<'_>) -> (String, ObjectId) {
+    // The encoded value is the namespace as a length prefixed, null terminated string followed
+    // by the ObjectId.
+    let buf = value.encode();
+    let ns_end = buf.len() - 12 - 1;
+    let namespace = String::from_utf8_lossy(&buf[4..ns_end]).into_owned();
+    let id = ObjectId::from_bytes(buf[buf.len() - 12..].try_into().expect("12 bytes"));
+    (namespace, id)
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{
+        Binary, Bson, DateTime, Decimal128, JavaScriptCodeWithScope, Regex, Timestamp, doc,
+        oid::ObjectId, rawdoc, spec::BinarySubtype,
+    };
+
+    use crate::{MutableDocument, MutableValue, ParsedDocument};
+
+    #[test]
+    fn display_value() {
+        let oid = ObjectId::from_bytes([1; 12]);
+        for (value, expected) in [
+            (MutableValue::from(1.5), "1.5".to_string()),
+            (
+                MutableValue::from(f64::NEG_INFINITY),
+                "-Infinity".to_string(),
+            ),
+            (MutableValue::from("a\"b\n"), "\"a\\\"b\\n\"".to_string()),
+            (MutableValue::from(oid), format!("ObjectId(\"{}\")", oid)),
+            (
+                MutableValue::from(DateTime::from_millis(0)),
+                "ISODate(\"1970-01-01T00:00:00Z\")".to_string(),
+            ),
+            (MutableValue::from(5i64), "NumberLong(5)".to_string()),
+            (
+                MutableValue::from(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: b"hello".to_vec(),
+                }),
+                "BinData(0, \"aGVsbG8=\")".to_string(),
+            ),
+            (
+                MutableValue::from(Regex {
+                    pattern: "a+".into(),
+                    options: "i".into(),
+                }),
+                "/a+/i".to_string(),
+            ),
+            (
+                MutableValue::from(Timestamp {
+                    time: 1,
+                    increment: 2,
+                }),
+                "Timestamp({ t: 1, i: 2 })".to_string(),
+            ),
+            (
+                MutableValue::from(Decimal128::from_bytes([0; 16])),
+                format!("NumberDecimal(\"{}\")", Decimal128::from_bytes([0; 16])),
+            ),
+            (
+                MutableValue::from(Bson::JavaScriptCodeWithScope(JavaScriptCodeWithScope {
+                    code: "f()".into(),
+                    scope: doc! { "x": 1 },
+                })),
+                "Code(\"f()\", { x: 1 })".to_string(),
+            ),
+            (MutableValue::MinKey, "MinKey()".to_string()),
+        ] {
+            assert_eq!(value.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn display_document() {
+        let raw = rawdoc! { "a": 1, "b c": [true, null], "d": {}, "e": [] };
+        let expected = "{ a: 1, \"b c\": [ true, null ], d: {}, e: [] }";
+        assert_eq!(
+            MutableValue::Document(MutableDocument::from(raw.as_ref())).to_string(),
+            expected
+        );
+        assert_eq!(
+            ParsedDocument::try_from(raw.as_ref()).unwrap().to_string(),
+            expected
+        );
+        assert_eq!(ParsedDocument::new().to_string(), "{}");
+    }
+}
@@ -0,0 +1,84 @@
+use sha2::{Digest, Sha256};
+
+use crate::{
+    MutableDocument, SerializeOptions, sort::sorted_document, write::invalid_data_ser_error,
+};
+
+impl MutableDocument<'_> {
+    /// Returns the SHA-256 hash of the encoded document.
+    ///
+    /// Encoded documents are hashed without copying. Documents with the same fields in a different
+    /// order have different fingerprints; see [`Self::fingerprint_canonical`].
+    pub fn fingerprint(&self) -> Result<[u8; 32], bson::ser::Error> {
+        Ok(match self {
+            Self::Borrowed(d) => Sha256::digest(d.as_bytes()).into(),
+            Self::Owned(p) => Sha256::digest(p.to_vec_with_options(&unlimited())?).into(),
+        })
+    }
+
+    /// Returns the SHA-256 hash of the encoded document after sorting keys at every nesting
+    /// level, so documents with the same fields in any order have the same fingerprint.
+    ///
+    /// Fails if any nested encoded document is malformed.
+    pub fn fingerprint_canonical(&self) -> Result<[u8; 32], bson::ser::Error> {
+        let sorted = sorted_document(self).map_err(invalid_data_ser_error)?;
+        Ok(Sha256::digest(sorted.to_vec_with_options(&unlimited())?).into())
+    }
+}
+
+fn unlimited() -> SerializeOptions {
+    SerializeOptions {
+        max_document_size: None,
+        ..SerializeOptions::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{JavaScriptCodeWithScope, doc, rawdoc};
+
+    use crate::{MutableDocument, MutableValue, ParsedDocument};
+
+    #[test]
+    fn fingerprint() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "d" } };
+        let borrowed = MutableDocument::from(raw.as_ref());
+        let owned = MutableDocument::from(ParsedDocument::try_from(raw.as_ref()).unwrap());
+        assert_eq!(
+            borrowed.fingerprint().unwrap(),
+            owned.fingerprint().unwrap()
+        );
+
+        let reordered = rawdoc! { "b": { "c": "d" }, "a": 1 };
+        let reordered = MutableDocument::from(reordered.as_ref());
+        assert_ne!(
+            borrowed.fingerprint().unwrap(),
+            reordered.fingerprint().unwrap()
+        );
+        assert_eq!(
+            borrowed.fingerprint_canonical().unwrap(),
+            reordered.fingerprint_canonical().unwrap()
+        );
+    }
+
+    #[test]
+    fn fingerprint_canonical_nested() {
+        let a = MutableDocument::from(doc! {
+            "x": [{ "b": 1, "a": 2 }],
+            "c": JavaScriptCodeWithScope { code: "f".into(), scope: doc! { "z": 1, "y": 2 } },
+        });
+        let mut b = MutableDocument::from(doc! {
+            "c": JavaScriptCodeWithScope { code: "f".into(), scope: doc! { "y": 2, "z": 1 } },
+            "x": [{ "a": 2, "b": 1 }],
+        });
+        assert_eq!(
+            a.fingerprint_canonical().unwrap(),
+            b.fingerprint_canonical().unwrap()
+        );
+        b.insert("x", MutableValue::Null).unwrap();
+        assert_ne!(
+            a.fingerprint_canonical().unwrap(),
+            b.fingerprint_canonical().unwrap()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use indexmap::IndexMap;
+
+use crate::{MutableValue, ParsedDocument};
+
+/// Errors that may occur when reconstructing a document with [`ParsedDocument::unflatten()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnflattenError {
+    /// A key refers to a value that another key also uses as a parent document or array, like
+    /// `"a"` and `"a.b"`.
+    Conflict(String),
+}
+
+impl std::fmt::Display for UnflattenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conflict(k) => write!(f, "key {} conflicts with another key", k),
+        }
+    }
+}
+
+impl std::error::Error for UnflattenError {}
+
+impl<'a> ParsedDocument<'a> {
+    /// Produce a single-level document where the keys of nested documents and the indices of
+    /// nested arrays are joined to their parent key with `.`, like `{"a.b": 1, "c.0": 2}`.
+    ///
+    /// Empty documents and arrays are kept as values so that the result may be reversed with
+    /// [`Self::unflatten()`]. Encoded documents and arrays are parsed, which may fail with a raw
+    /// BSON parsing error.
+    pub fn flatten(&self) -> Result<ParsedDocument<'static>, bson::raw::Error> {
+        let mut flat = ParsedDocument::new();
+        for (key, value) in self.iter() {
+            flatten_into(key.to_string(), value, &mut flat)?;
+        }
+        Ok(flat)
+    }
+
+    /// Reconstruct a nested document from a document with dot-notation keys, the inverse of
+    /// [`Self::flatten()`].
+    ///
+    /// A set of keys that are exactly the indices `0..n` becomes an array; any other keys become
+    /// a document. Fails if a key is also used as the parent of another key.
+    pub fn unflatten(flat: ParsedDocument<'a>) -> Result<ParsedDocument<'a>, UnflattenError> {
+        let mut root = IndexMap::new();
+        for (key, value) in flat {
+            let mut node = &mut root;
+            let mut segments = key.split('.').peekable();
+            while let Some(segment) = segments.next() {
+                if segments.peek().is_none() {
+                    if node
+                        .insert(segment.to_string(), Node::Leaf(value))
+                        .is_some()
+                    {
+                        return Err(UnflattenError::Conflict(key.into_owned()));
+                    }
+                    break;
+                }
+                node = match node
+                    .entry(segment.to_string())
+                    .or_insert_with(|| Node::Branch(IndexMap::new()))
+                {
+                    Node::Branch(children) => children,
+                    Node::Leaf(_) => return Err(UnflattenError::Conflict(key.into_owned())),
+                };
+            }
+        }
+        Ok(build_document(root))
+    }
+}
+
+fn flatten_into(
+    key: String,
+    value: &MutableValue<'_>,
+    flat: &mut ParsedDocument<'static>,
+) -> Result<(), bson::raw::Error> {
+    match value {
+        MutableValue::Document(d) if !d.is_empty() => {
+            for (k, v) in d.as_parsed()?.iter() {
+                flatten_into(format!("{}.{}", key, k), v, flat)?;
+            }
+        }
+        MutableValue::Array(a) if !a.is_empty() => {
+            for (i, v) in a.iter().enumerate() {
+                flatten_into(format!("{}.{}", key, i), v?.as_ref(), flat)?;
+            }
+        }
+        v => {
+            flat.insert(key, v.clone().into_owned()?);
+        }
+    }
+    Ok(())
+}
+
+enum Node<'a> {
+    Leaf(MutableValue<'a>),
+    Branch(IndexMap<String, Node<'a>>),
+}
+
+fn build_document(children: IndexMap<String, Node<'_>>) -> ParsedDocument<'_> {
+    children
+        .into_iter()
+        .map(|(k, v)| (k, build_value(v)))
+        .collect()
+}
+
+fn build_value(node: Node<'_>) -> MutableValue<'_> {
+    let mut children = match node {
+        Node::Leaf(v) => return v,
+        Node::Branch(children) => children,
+    };
+    let is_array = (0..children.len()).all(|i| children.contains_key(i.to_string().as_str()));
+    if is_array {
+        children.sort_by_cached_key(|k, _| k.parse::<usize>().expect("array index"));
+        children
+            .into_values()
+            .map(build_value)
+            .collect::<Vec<_>>()
+            .into()
+    } else {
+        build_document(children).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::ParsedDocument;
+
+    use super::UnflattenError;
+
+    #[test]
+    fn flatten() {
+        let raw = rawdoc! { "a": { "b": 1, "c": [2, [3], { "d": 4 }] }, "e": {}, "f": [], "g": 5 };
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        let flat = doc.flatten().unwrap();
+        assert_eq!(
+            flat.to_vec().unwrap(),
+            rawdoc! {
+                "a.b": 1,
+                "a.c.0": 2,
+                "a.c.1.0": 3,
+                "a.c.2.d": 4,
+                "e": {},
+                "f": [],
+                "g": 5,
+            }
+            .as_bytes()
+        );
+        assert_eq!(
+            ParsedDocument::unflatten(flat).unwrap().to_vec().unwrap(),
+            raw.as_bytes()
+        );
+    }
+
+    #[test]
+    fn unflatten_array_order() {
+        let flat = parsed_doc! { "a.1": "y", "a.0": "x", "b.0": 1, "b.2": 2 };
+        assert_eq!(
+            ParsedDocument::unflatten(flat).unwrap().to_vec().unwrap(),
+            rawdoc! { "a": ["x", "y"], "b": { "0": 1, "2": 2 } }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn unflatten_conflict() {
+        assert_eq!(
+            ParsedDocument::unflatten(parsed_doc! { "a": 1, "a.b": 2 }).unwrap_err(),
+            UnflattenError::Conflict("a.b".to_string())
+        );
+        assert_eq!(
+            ParsedDocument::unflatten(parsed_doc! { "a.b": 1, "a": 2 }).unwrap_err(),
+            UnflattenError::Conflict("a".to_string())
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    MutableArray, MutableBinary, MutableDbPointer, MutableDocument, MutableJavaScriptCodeWithScope,
+    MutableRegex, MutableValue,
+};
+
+/// Hashes are consistent with [`PartialEq`]: values that compare equal produce the same hash.
+///
+/// `Double` values are hashed with [`f64::to_bits()`], matching the bitwise comparison used by
+/// `PartialEq`. This means `NaN` values with the same bit pattern are equal and hash the same, but
+/// `0.0` and `-0.0` are neither equal nor guaranteed to hash the same.
+///
+/// Documents and arrays are hashed element by element so that encoded and parsed representations
+/// of the same value hash the same. Hashing an encoded document or array stops at the first
+/// malformed element.
+impl Hash for MutableValue<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.element_type() as u8).hash(state);
+        match self {
+            Self::Double(v) => v.to_bits().hash(state),
+            Self::String(v) | Self::JavaScriptCode(v) | Self::Symbol(v) => v.hash(state),
+            Self::Document(v) => v.hash(state),
+            Self::Array(v) => v.hash(state),
+            Self::Binary(v) => v.hash(state),
+            Self::ObjectId(v) => v.hash(state),
+            Self::Boolean(v) => v.hash(state),
+            Self::DateTime(v) => v.hash(state),
+            Self::RegularExpression(v) => v.hash(state),
+            Self::DbPointer(v) => v.hash(state),
+            Self::JavaScriptCodeWithScope(v) => v.hash(state),
+            Self::Int32(v) => v.hash(state),
+            Self::Timestamp(v) => v.hash(state),
+            Self::Int64(v) => v.hash(state),
+            Self::Decimal128(v) => v.hash(state),
+            Self::Undefined | Self::Null | Self::MinKey | Self::MaxKey => {}
+        }
+    }
+}
+
+impl Hash for MutableDocument<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Self::Borrowed(d) => {
+                for (k, v) in d.into_iter().map_while(Result::ok) {
+                    k.hash(state);
+                    MutableValue::from(v).hash(state);
+                }
+            }
+            Self::Owned(p) => {
+                for (k, v) in p.iter() {
+                    k.hash(state);
+                    v.hash(state);
+                }
+            }
+        }
+    }
+}
+
+impl Hash for MutableArray<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for v in self.iter().map_while(Result::ok) {
+            v.hash(state);
+        }
+    }
+}
+
+impl Hash for MutableBinary<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let (bytes, subtype) = self.parts();
+        u8::from(subtype).hash(state);
+        bytes.hash(state);
+    }
+}
+
+impl Hash for MutableRegex<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts().hash(state);
+    }
+}
+
+impl Hash for MutableDbPointer<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.encode().hash(state);
+    }
+}
+
+impl Hash for MutableJavaScriptCodeWithScope<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.code().hash(state);
+        if let Ok(scope) = self.encoded_scope() {
+            scope.hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::hash::{BuildHasher, Hash, RandomState};
+
+    use bson::rawdoc;
+
+    use crate::{MutableArray, MutableDocument, MutableValue, ParsedDocument};
+
+    fn hash<T: Hash>(state: &RandomState, value: &T) -> u64 {
+        state.hash_one(value)
+    }
+
+    #[test]
+    fn hash_consistent_with_eq() {
+        let state = RandomState::new();
+        let raw = rawdoc! { "a": 1, "b": { "c": [1.5, "x"] }, "d": null };
+        let borrowed = MutableValue::Document(raw.as_ref().into());
+        let owned =
+            MutableValue::Document(MutableDocument::from(raw.as_ref()).into_owned().unwrap());
+        assert_eq!(borrowed, owned);
+        assert_eq!(hash(&state, &borrowed), hash(&state, &owned));
+
+        let array_raw = rawdoc! { "a": [1, "two"] };
+        let borrowed = MutableArray::from(array_raw.get_array("a").unwrap());
+        let owned = MutableArray::from(vec![MutableValue::from(1), MutableValue::from("two")]);
+        assert_eq!(hash(&state, &borrowed), hash(&state, &owned));
+
+        let nan = MutableValue::Double(f64::NAN);
+        assert_eq!(nan, nan.clone());
+        assert_eq!(hash(&state, &nan), hash(&state, &nan.clone()));
+    }
+
+    #[test]
+    fn hash_distinguishes_types() {
+        let state = RandomState::new();
+        assert_ne!(
+            hash(&state, &MutableValue::Int32(1)),
+            hash(&state, &MutableValue::Int64(1))
+        );
+        assert_ne!(
+            hash(&state, &MutableValue::from("a")),
+            hash(&state, &MutableValue::Symbol("a".into()))
+        );
+        assert_ne!(
+            hash(&state, &MutableValue::from(ParsedDocument::new())),
+            hash(&state, &MutableValue::from(Vec::<MutableValue>::new()))
+        );
+    }
+}
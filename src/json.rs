@@ -0,0 +1,341 @@
+use bson::{Bson, RawDocument};
+use serde_json::{Map, Number, Value, json};
+
+use crate::{
+    MutableArray, MutableDocument, MutableJavaScriptCodeWithScope, MutableValue, ParsedDocument,
+};
+
+/// Errors that may occur when decoding MongoDB Extended JSON.
+#[derive(Clone, Debug)]
+pub enum ExtendedJsonError {
+    /// The input contains an invalid extended JSON value, like `{"$numberLong": 5}`.
+    Invalid(bson::extjson::de::Error),
+}
+
+impl std::fmt::Display for ExtendedJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExtendedJsonError {}
+
+impl From<bson::extjson::de::Error> for ExtendedJsonError {
+    fn from(value: bson::extjson::de::Error) -> Self {
+        Self::Invalid(value)
+    }
+}
+
+impl MutableValue<'_> {
+    /// Convert to canonical MongoDB Extended JSON v2, which preserves the type of every value.
+    ///
+    /// Encoded documents and arrays are truncated at the first malformed element.
+    pub fn to_extended_json_v2(&self) -> Value {
+        to_json_v2(self)
+    }
+
+    /// Write the value as canonical MongoDB Extended JSON v2; an alternative to the shell format
+    /// used by [`Display`](std::fmt::Display).
+    pub fn fmt_extended_json(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_extended_json_v2())
+    }
+
+    /// Decode MongoDB Extended JSON v2 in either canonical or relaxed mode.
+    ///
+    /// May fail if value contains a malformed extended JSON wrapper.
+    pub fn from_extended_json_v2(
+        value: &Value,
+    ) -> Result<MutableValue<'static>, ExtendedJsonError> {
+        Ok(Bson::try_from(value.clone())?.into())
+    }
+}
+
+impl ParsedDocument<'_> {
+    /// Encode the document as a canonical MongoDB Extended JSON v2 string.
+    ///
+    /// See [`MutableValue::to_extended_json_v2`].
+    pub fn to_extended_json_v2_string(&self) -> String {
+        self.to_extended_json_v2().to_string()
+    }
+
+    /// Write the document as canonical MongoDB Extended JSON v2; an alternative to the shell
+    /// format used by [`Display`](std::fmt::Display).
+    pub fn fmt_extended_json(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_extended_json_v2())
+    }
+
+    fn to_extended_json_v2(&self) -> Value {
+        Value::Object(
+            self.iter()
+                .map(|(k, v)| (k.to_string(), v.to_extended_json_v2()))
+                .collect(),
+        )
+    }
+}
+
+/// Converts plain JSON without interpreting extended JSON keys like `$oid`.
+///
+/// Integers that fit in 32 bits become `Int32`, other integers that fit in 64 bits become
+/// `Int64`, and all remaining numbers become `Double`.
+impl From<Value> for MutableValue<'static> {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(v) => Self::Boolean(v),
+            Value::Number(v) => match v.as_i64() {
+                Some(i) => i32::try_from(i).map_or(Self::Int64(i), Self::Int32),
+                None => Self::Double(v.as_f64().unwrap_or(f64::NAN)),
+            },
+            Value::String(v) => Self::String(v.into()),
+            Value::Array(v) => Self::Array(MutableArray::Owned(
+                v.into_iter().map(MutableValue::from).collect(),
+            )),
+            Value::Object(v) => Self::Document(MutableDocument::Owned(
+                v.into_iter()
+                    .map(|(k, v)| (k, MutableValue::from(v)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// Converts to MongoDB Extended JSON v1 in strict mode.
+///
+/// `Int32`, `Double`, `Boolean`, `String` and `Symbol` values are represented as plain JSON;
+/// other BSON-specific types use wrappers like `{"$oid": "..."}` and `{"$numberLong": "..."}`.
+/// Non-finite doubles cannot be represented and become `null`. Encoded documents and arrays are
+/// truncated at the first malformed element.
+impl From<&MutableValue<'_>> for Value {
+    fn from(value: &MutableValue<'_>) -> Self {
+        to_json_v1(value)
+    }
+}
+
+fn to_json_v1(value: &MutableValue<'_>) -> Value {
+    match value {
+        MutableValue::Double(v) => Number::from_f64(*v).map_or(Value::Null, Value::Number),
+        MutableValue::String(v) | MutableValue::Symbol(v) => Value::String(v.to_string()),
+        MutableValue::Document(v) => document_to_json(v, to_json_v1),
+        MutableValue::Array(v) => array_to_json(v, to_json_v1),
+        MutableValue::Boolean(v) => Value::Bool(*v),
+        MutableValue::Null => Value::Null,
+        MutableValue::Int32(v) => Value::from(*v),
+        MutableValue::DateTime(v) => json!({ "$date": v.timestamp_millis() }),
+        MutableValue::JavaScriptCodeWithScope(v) => code_with_scope_to_json(v, to_json_v1),
+        _ => match scalar_to_canonical_json(value) {
+            // v1 spells out the fields of these types at the top level.
+            Value::Object(mut m) => {
+                if let Some(Value::Object(b)) = m.remove("$binary") {
+                    json!({ "$binary": b["base64"], "$type": b["subType"] })
+                } else if let Some(Value::Object(r)) = m.remove("$regularExpression") {
+                    json!({ "$regex": r["pattern"], "$options": r["options"] })
+                } else if let Some(p) = m.remove("$dbPointer") {
+                    p
+                } else {
+                    Value::Object(m)
+                }
+            }
+            v => v,
+        },
+    }
+}
+
+fn to_json_v2(value: &MutableValue<'_>) -> Value {
+    match value {
+        MutableValue::Document(v) => document_to_json(v, to_json_v2),
+        MutableValue::Array(v) => array_to_json(v, to_json_v2),
+        MutableValue::JavaScriptCodeWithScope(v) => code_with_scope_to_json(v, to_json_v2),
+        _ => scalar_to_canonical_json(value),
+    }
+}
+
+/// Converts a value that is not a document, array or code with scope to canonical extended JSON
+/// v2 using the `bson` crate.
+fn scalar_to_canonical_json(value: &MutableValue<'_>) -> Value {
+    Bson::try_from(value.clone())
+        .expect("scalar values always convert")
+        .into_canonical_extjson()
+}
+
+fn document_to_json(doc: &MutableDocument<'_>, f: fn(&MutableValue<'_>) -> Value) -> Value {
+    Value::Object(
+        doc.iter()
+            .map_while(Result::ok)
+            .map(|(k, v)| (k.to_string(), f(&v)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+fn array_to_json(array: &MutableArray<'_>, f: fn(&MutableValue<'_>) -> Value) -> Value {
+    Value::Array(array.iter().map_while(Result::ok).map(|v| f(&v)).collect())
+}
+
+fn code_with_scope_to_json(
+    value: &MutableJavaScriptCodeWithScope<'_>,
+    f: fn(&MutableValue<'_>) -> Value,
+) -> Value {
+    let scope = value.encoded_scope().ok();
+    let scope = match scope.as_deref().map(RawDocument::from_bytes) {
+        Some(Ok(scope)) => document_to_json(&MutableDocument::Borrowed(scope), f),
+        _ => Value::Object(Map::new()),
+    };
+    json!({ "$code": value.code(), "$scope": scope })
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{
+        DateTime, Decimal128, Regex, Timestamp, oid::ObjectId, rawdoc, spec::BinarySubtype,
+    };
+    use serde_json::{Value, json};
+
+    use crate::{MutableBinary, MutableDocument, MutableValue, ParsedDocument};
+
+    use super::ExtendedJsonError;
+
+    #[test]
+    fn from_json() {
+        let value = MutableValue::from(json!({
+            "null": null,
+            "b": true,
+            "i32": 1,
+            "i64": 1i64 << 40,
+            "u64": u64::MAX,
+            "f": 1.5,
+            "s": "x",
+            "a": [1, { "oid": { "$oid": "x" } }],
+        }));
+        let MutableValue::Document(doc) = value else {
+            panic!("expected document");
+        };
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! {
+                "null": null,
+                "b": true,
+                "i32": 1,
+                "i64": 1i64 << 40,
+                "u64": u64::MAX as f64,
+                "f": 1.5,
+                "s": "x",
+                "a": [1, { "oid": { "$oid": "x" } }],
+            }
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn to_json_v1() {
+        let oid = ObjectId::from_bytes([1; 12]);
+        let raw = rawdoc! {
+            "f": 1.5,
+            "i32": 1,
+            "i64": 2i64,
+            "oid": oid,
+            "date": DateTime::from_millis(1000),
+            "re": Regex { pattern: "a+".into(), options: "i".into() },
+            "ts": Timestamp { time: 1, increment: 2 },
+            "dec": Decimal128::from_bytes([0; 16]),
+            "nested": { "a": [true, null, "s"] },
+        };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        doc.insert(
+            "bin",
+            MutableBinary::Owned(bson::Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: b"hi".to_vec(),
+            }),
+        )
+        .unwrap();
+        doc.insert("nan", f64::NAN).unwrap();
+        assert_eq!(
+            Value::from(&MutableValue::Document(doc)),
+            json!({
+                "f": 1.5,
+                "i32": 1,
+                "i64": { "$numberLong": "2" },
+                "oid": { "$oid": oid.to_hex() },
+                "date": { "$date": 1000 },
+                "re": { "$regex": "a+", "$options": "i" },
+                "ts": { "$timestamp": { "t": 1, "i": 2 } },
+                "dec": { "$numberDecimal": Decimal128::from_bytes([0; 16]).to_string() },
+                "nested": { "a": [true, null, "s"] },
+                "bin": { "$binary": "aGk=", "$type": "00" },
+                "nan": null,
+            })
+        );
+    }
+
+    #[test]
+    fn extended_json_v2_round_trip() {
+        let oid = ObjectId::from_bytes([1; 12]);
+        let raw = rawdoc! {
+            "f": 1.0,
+            "i32": 1,
+            "i64": 2i64,
+            "oid": oid,
+            "date": DateTime::from_millis(1000),
+            "re": Regex { pattern: "a+".into(), options: "i".into() },
+            "ts": Timestamp { time: 1, increment: 2 },
+            "sym": bson::RawBson::Symbol("s".into()),
+            "nested": { "a": [true, null, "s", bson::RawBson::MinKey] },
+        };
+        let value = MutableValue::Document(raw.as_ref().into());
+        let json = value.to_extended_json_v2();
+        assert_eq!(json["f"], json!({ "$numberDouble": "1.0" }));
+        assert_eq!(json["i32"], json!({ "$numberInt": "1" }));
+        assert_eq!(json["date"], json!({ "$date": { "$numberLong": "1000" } }));
+        assert_eq!(json["sym"], json!({ "$symbol": "s" }));
+        assert_eq!(
+            MutableValue::from_extended_json_v2(&json).unwrap(),
+            MutableValue::Document(raw.as_ref().into())
+        );
+
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&doc.to_extended_json_v2_string()).unwrap(),
+            json
+        );
+    }
+
+    #[test]
+    fn fmt_extended_json() {
+        struct ExtJson<'a>(&'a ParsedDocument<'a>);
+        impl std::fmt::Display for ExtJson<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_extended_json(f)
+            }
+        }
+        let raw = rawdoc! { "a": 1 };
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(ExtJson(&doc).to_string(), r#"{"a":{"$numberInt":"1"}}"#);
+        assert_eq!(doc.to_string(), "{ a: 1 }");
+    }
+
+    #[test]
+    fn from_extended_json_v2_relaxed() {
+        let value = MutableValue::from_extended_json_v2(&json!({
+            "i": 1,
+            "l": 1i64 << 40,
+            "date": { "$date": "1970-01-01T00:00:01Z" },
+        }))
+        .unwrap();
+        let MutableValue::Document(doc) = value else {
+            panic!("expected document");
+        };
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "i": 1, "l": 1i64 << 40, "date": DateTime::from_millis(1000) }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn from_extended_json_v2_invalid() {
+        assert!(matches!(
+            MutableValue::from_extended_json_v2(&json!({ "$numberLong": 5 })),
+            Err(ExtendedJsonError::Invalid(_))
+        ));
+    }
+}
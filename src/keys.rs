@@ -0,0 +1,203 @@
+use crate::{MutableDocument, MutableValue, ParsedDocument};
+
+/// Rules for which document keys are allowed by [`MutableDocument::validate_keys`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Keys may not contain `.` or begin with `$` at any depth.
+    Strict,
+    /// Keys may not contain `.`, and may only begin with `$` in nested documents, as allowed by
+    /// MongoDB 5.0 and later.
+    Mongo50Plus,
+}
+
+impl KeyPolicy {
+    fn allows(self, key: &str, top_level: bool) -> bool {
+        !key.contains('.') && (!key.starts_with('$') || (self == Self::Mongo50Plus && !top_level))
+    }
+}
+
+/// Errors that may occur when validating document keys.
+#[derive(Clone, Debug)]
+pub enum KeyValidationError {
+    /// A document or array could not be parsed.
+    Raw(bson::raw::Error),
+    /// The key at this path is not allowed by the policy.
+    InvalidKey(String),
+}
+
+impl std::fmt::Display for KeyValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw(e) => write!(f, "{}", e),
+            Self::InvalidKey(p) => write!(f, "invalid key at {}", p),
+        }
+    }
+}
+
+impl std::error::Error for KeyValidationError {}
+
+impl From<bson::raw::Error> for KeyValidationError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Check that every key in the document and any nested documents is allowed by policy,
+    /// treating this document as the top level.
+    ///
+    /// Encoded documents are scanned without parsing. Fails with the path to the first invalid
+    /// key, or with a raw BSON parsing error.
+    pub fn validate_keys(&self, policy: KeyPolicy) -> Result<(), KeyValidationError> {
+        validate_document(self, policy, None)
+    }
+}
+
+impl<'a> ParsedDocument<'a> {
+    /// Check that every key in the document and any nested documents is allowed by policy,
+    /// treating this document as the top level.
+    ///
+    /// Fails with the path to the first invalid key, or with a raw BSON parsing error.
+    pub fn validate_keys(&self, policy: KeyPolicy) -> Result<(), KeyValidationError> {
+        for (k, v) in self.iter() {
+            validate_field(k, v, policy, None)?;
+        }
+        Ok(())
+    }
+
+    /// Insert a value for key like [`ParsedDocument::insert`], but only if key and any keys nested
+    /// in value are allowed by policy. This document is treated as the top level.
+    ///
+    /// The document is not modified if validation fails.
+    pub fn insert_validated<V: Into<MutableValue<'static>>>(
+        &mut self,
+        key: impl Into<String>,
+        value: V,
+        policy: KeyPolicy,
+    ) -> Result<Option<MutableValue<'a>>, KeyValidationError> {
+        let key = key.into();
+        let value = value.into();
+        validate_field(&key, &value, policy, None)?;
+        Ok(self.insert(key, value))
+    }
+}
+
+fn join(prefix: Option<&str>, key: &str) -> String {
+    match prefix {
+        Some(p) => format!("{}.{}", p, key),
+        None => key.to_string(),
+    }
+}
+
+fn validate_field(
+    key: &str,
+    value: &MutableValue<'_>,
+    policy: KeyPolicy,
+    prefix: Option<&str>,
+) -> Result<(), KeyValidationError> {
+    let path = join(prefix, key);
+    if !policy.allows(key, prefix.is_none()) {
+        return Err(KeyValidationError::InvalidKey(path));
+    }
+    validate_value(value, policy, &path)
+}
+
+fn validate_document(
+    doc: &MutableDocument<'_>,
+    policy: KeyPolicy,
+    prefix: Option<&str>,
+) -> Result<(), KeyValidationError> {
+    for e in doc.iter() {
+        let (k, v) = e?;
+        validate_field(k, &v, policy, prefix)?;
+    }
+    Ok(())
+}
+
+fn validate_value(
+    value: &MutableValue<'_>,
+    policy: KeyPolicy,
+    path: &str,
+) -> Result<(), KeyValidationError> {
+    match value {
+        MutableValue::Document(d) => validate_document(d, policy, Some(path)),
+        MutableValue::Array(a) => {
+            // Array keys are indices so only the elements need to be checked.
+            for (i, v) in a.iter().enumerate() {
+                validate_value(v?.as_ref(), policy, &join(Some(path), &i.to_string()))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableDocument, ParsedDocument};
+
+    use super::{KeyPolicy, KeyValidationError};
+
+    #[test]
+    fn validate_keys() {
+        let raw = rawdoc! { "a": { "b": [1, { "c": 2 }] }, "d": "$e" };
+        let doc = MutableDocument::from(raw.as_ref());
+        assert!(doc.validate_keys(KeyPolicy::Strict).is_ok());
+        assert!(doc.validate_keys(KeyPolicy::Mongo50Plus).is_ok());
+    }
+
+    #[test]
+    fn validate_keys_invalid() {
+        let raw = rawdoc! { "a": { "b": [1, { "$c": 2 }] } };
+        let doc = MutableDocument::from(raw.as_ref());
+        assert!(matches!(
+            doc.validate_keys(KeyPolicy::Strict),
+            Err(KeyValidationError::InvalidKey(p)) if p == "a.b.1.$c"
+        ));
+        assert!(doc.validate_keys(KeyPolicy::Mongo50Plus).is_ok());
+
+        let raw = rawdoc! { "$a": 1 };
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert!(matches!(
+            doc.validate_keys(KeyPolicy::Mongo50Plus),
+            Err(KeyValidationError::InvalidKey(p)) if p == "$a"
+        ));
+
+        let raw = rawdoc! { "a": { "b.c": 1 } };
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert!(matches!(
+            doc.validate_keys(KeyPolicy::Mongo50Plus),
+            Err(KeyValidationError::InvalidKey(p)) if p == "a.b.c"
+        ));
+    }
+
+    #[test]
+    fn insert_validated() {
+        let mut doc = ParsedDocument::new();
+        assert!(
+            doc.insert_validated("a", ParsedDocument::new(), KeyPolicy::Strict)
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            doc.insert_validated("$b", 1, KeyPolicy::Mongo50Plus),
+            Err(KeyValidationError::InvalidKey(p)) if p == "$b"
+        ));
+        let raw = rawdoc! { "$c": 1 };
+        let nested = ParsedDocument::try_from(raw.as_ref())
+            .unwrap()
+            .into_owned()
+            .unwrap();
+        assert!(matches!(
+            doc.insert_validated("b", nested.clone(), KeyPolicy::Strict),
+            Err(KeyValidationError::InvalidKey(p)) if p == "b.$c"
+        ));
+        assert!(
+            doc.insert_validated("b", nested, KeyPolicy::Mongo50Plus)
+                .is_ok()
+        );
+        assert_eq!(doc.len(), 2);
+    }
+}
@@ -1,15 +1,75 @@
+#[macro_use]
+mod macros;
+
+mod builder;
+mod cmp;
+mod display;
+#[cfg(feature = "sha2")]
+mod fingerprint;
+mod flatten;
+mod hash;
+#[cfg(feature = "serde_json")]
+mod json;
+mod keys;
+mod memory;
+mod merge;
+mod nesting;
+mod overlay;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod parsed_document;
-
-use std::{borrow::Cow, sync::Arc};
+mod patch;
+mod path;
+mod pointer;
+mod project;
+mod ring_buffer;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sort;
+mod stream;
+#[cfg(feature = "tokio")]
+mod tokio_impl;
+mod track;
+mod update;
+#[cfg(feature = "uuid")]
+mod uuid_impl;
+mod visit;
+mod write;
+
+use std::{borrow::Cow, ops::RangeBounds};
 
 use bson::{
-    Binary, Bson, DateTime, Decimal128, Document, JavaScriptCodeWithScope, RawArray, RawBinaryRef,
-    RawBsonRef, RawDbPointerRef, RawDocument, RawJavaScriptCodeWithScopeRef, RawRegexRef, Regex,
-    Timestamp, oid::ObjectId, spec::ElementType,
+    Binary, Bson, DateTime, DbPointer, Decimal128, Document, JavaScriptCodeWithScope, RawArray,
+    RawBinaryRef, RawBson, RawBsonRef, RawDbPointerRef, RawDocument, RawDocumentBuf,
+    RawJavaScriptCodeWithScopeRef, RawRegexRef, Regex, Timestamp,
+    oid::ObjectId,
+    raw::{RawArrayIter, RawIter},
+    spec::{BinarySubtype, ElementType},
 };
 
+pub use builder::{MutableArrayBuilder, MutableDocumentBuilder};
 use bytes::BufMut;
-pub use parsed_document::ParsedDocument;
+pub use cmp::compare_values;
+pub use flatten::UnflattenError;
+#[cfg(feature = "serde_json")]
+pub use json::ExtendedJsonError;
+pub use keys::{KeyPolicy, KeyValidationError};
+pub use merge::{ArrayStrategy, ConflictError, ConflictPolicy, MergeError, MergeStrategy};
+pub use nesting::NestingError;
+pub use overlay::{OverlayDocument, overlay};
+pub use parsed_document::{
+    Entry, OccupiedEntry, ParsedDocument, ParsedDocumentIntoIter, RenameError, Snapshot,
+    VacantEntry,
+};
+pub use patch::{PatchError, PatchOperation};
+pub use path::PathError;
+pub use pointer::PointerError;
+pub use project::ProjectionError;
+pub use ring_buffer::DocumentRingBuffer;
+pub use stream::{BsonStreamReader, BsonStreamWriter, ReadError};
+pub use track::{Change, TrackedDocument};
+pub use update::{UpdateError, diff};
+pub use visit::{Visitor, VisitorMut};
 
 fn raw_cstr_len(s: &str) -> usize {
     s.len() + 1
@@ -39,13 +99,122 @@ fn put_raw_str(s: &str, buf: &mut impl BufMut) {
     buf.put_u8(0);
 }
 
+/// The default maximum encoded document size used by `to_vec()`.
+const DEFAULT_MAX_DOCUMENT_SIZE: usize = 32 << 20;
+
+/// Options that control encoding of documents with `to_vec_with_options()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Maximum encoded size of a document in bytes, or `None` to disable the check. BSON
+    /// documents may never exceed `i32::MAX` bytes regardless of this setting.
+    ///
+    /// Defaults to 32 MiB.
+    pub max_document_size: Option<usize>,
+    /// Maximum depth of nested documents and arrays as measured by
+    /// [`MutableDocument::max_nesting_depth`], or `None` to disable the check.
+    ///
+    /// Defaults to `None`.
+    pub max_nesting_depth: Option<usize>,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            max_document_size: Some(DEFAULT_MAX_DOCUMENT_SIZE),
+            max_nesting_depth: None,
+        }
+    }
+}
+
+/// The encoded length of a field or element, including type and key, assumed by
+/// [`MutableValue::estimate_bson_size()`] for parsed documents and arrays.
+const ESTIMATED_FIELD_LEN: usize = 16;
+
+fn estimate_document_len(doc: &ParsedDocument<'_>) -> usize {
+    doc.cached_raw_len()
+        .unwrap_or(5 + doc.len() * ESTIMATED_FIELD_LEN)
+}
+
+/// Returns true if the coefficient of a BID encoded decimal is zero.
+fn decimal128_is_zero(value: &Decimal128) -> bool {
+    let bits = u128::from_le_bytes(value.bytes());
+    let combination = (bits >> 122) & 0x1f;
+    if combination >= 0x1e {
+        // Infinity or NaN.
+        false
+    } else if (bits >> 125) & 0x3 == 0x3 {
+        // Coefficients with an implied 0b100 prefix exceed the maximum and are treated as zero.
+        true
+    } else {
+        bits & ((1 << 113) - 1) == 0
+    }
+}
+
+fn count_type(counts: &mut Vec<(ElementType, usize)>, element_type: ElementType) {
+    match counts.iter_mut().find(|(t, _)| *t == element_type) {
+        Some((_, count)) => *count += 1,
+        None => counts.push((element_type, 1)),
+    }
+}
+
+fn count_types_recursive(
+    counts: &mut Vec<(ElementType, usize)>,
+    value: &MutableValue<'_>,
+) -> Result<(), bson::raw::Error> {
+    count_type(counts, value.element_type());
+    match value {
+        MutableValue::Document(d) => {
+            for field in d.iter() {
+                count_types_recursive(counts, &field?.1)?;
+            }
+        }
+        MutableValue::Array(a) => {
+            for v in a.iter() {
+                count_types_recursive(counts, v?.as_ref())?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Encode a value of len bytes into a new buffer using put, failing if len exceeds the maximum
+/// document length in options.
+fn encode_to_vec(
+    len: usize,
+    options: &SerializeOptions,
+    put: impl FnOnce(&mut Vec<u8>) -> Result<(), bson::ser::Error>,
+) -> Result<Vec<u8>, bson::ser::Error> {
+    // TODO: cache the raw length in all MutableValues where length computation is non-trivial.
+    // ParsedDocument caches its length but MutableArray::Owned does not, so raw_len() is called
+    // twice on owned arrays: once to size the output buffer and once to emit the buffer, and
+    // this will be done for all owned arrays down the tree.
+    check_encoded_len(len, options)?;
+    let mut buf = Vec::with_capacity(len);
+    put(&mut buf).map(|_| buf)
+}
+
+/// Fail if an encoded value of len bytes exceeds the maximum document length in options.
+fn check_encoded_len(len: usize, options: &SerializeOptions) -> Result<(), bson::ser::Error> {
+    let max_len = options
+        .max_document_size
+        .unwrap_or(usize::MAX)
+        .min(i32::MAX as usize);
+    if len > max_len {
+        return Err(write::invalid_data_ser_error(
+            "Exceeded max document length",
+        ));
+    }
+    Ok(())
+}
+
 /// A BSON Value that is mutable.
 ///
 /// `Document` and `Array` types may refer to owned/unowned raw BSON types or to a parsed
 /// representation that allows mutation of individual elements. For other variable size types we
 /// either use a [`Cow`] or wrap owned + reference types. Fixed size types use the same inline
 /// representation as they do in [`bson::Bson`] and [`bson::RawBson`].
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum MutableValue<'a> {
     Double(f64),
     String(Cow<'a, str>),
@@ -56,10 +225,11 @@ pub enum MutableValue<'a> {
     ObjectId(ObjectId),
     Boolean(bool),
     DateTime(DateTime),
+    #[default]
     Null,
     RegularExpression(MutableRegex<'a>),
     /// DbPointers cannot be mutated using the `bson` crate.
-    DbPointer(RawDbPointerRef<'a>),
+    DbPointer(MutableDbPointer<'a>),
     JavaScriptCode(Cow<'a, str>),
     Symbol(Cow<'a, str>),
     JavaScriptCodeWithScope(MutableJavaScriptCodeWithScope<'a>),
@@ -99,6 +269,97 @@ impl<'a> MutableValue<'a> {
         }
     }
 
+    /// Returns a human readable name for the type of this value, suitable for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Double(_) => "Double",
+            Self::String(_) => "String",
+            Self::Document(_) => "Document",
+            Self::Array(_) => "Array",
+            Self::Binary(_) => "Binary",
+            Self::Undefined => "Undefined",
+            Self::ObjectId(_) => "ObjectId",
+            Self::Boolean(_) => "Boolean",
+            Self::DateTime(_) => "DateTime",
+            Self::Null => "Null",
+            Self::RegularExpression(_) => "RegularExpression",
+            Self::DbPointer(_) => "DbPointer",
+            Self::JavaScriptCode(_) => "JavaScriptCode",
+            Self::Symbol(_) => "Symbol",
+            Self::JavaScriptCodeWithScope(_) => "JavaScriptCodeWithScope",
+            Self::Int32(_) => "Int32",
+            Self::Timestamp(_) => "Timestamp",
+            Self::Int64(_) => "Int64",
+            Self::Decimal128(_) => "Decimal128",
+            Self::MinKey => "MinKey",
+            Self::MaxKey => "MaxKey",
+        }
+    }
+
+    /// Returns true if this value is `Null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns true if this value is `Undefined`.
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Self::Undefined)
+    }
+
+    /// Returns true if this value is `MinKey`.
+    pub fn is_min_key(&self) -> bool {
+        matches!(self, Self::MinKey)
+    }
+
+    /// Returns true if this value is `MaxKey`.
+    pub fn is_max_key(&self) -> bool {
+        matches!(self, Self::MaxKey)
+    }
+
+    /// Returns true if this value is truthy in MongoDB aggregation expressions.
+    ///
+    /// `false`, numeric zero, `null` and `undefined` are falsy; all other values, including empty
+    /// documents, arrays and strings, are truthy. `NaN` is truthy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Boolean(v) => *v,
+            Self::Int32(v) => *v != 0,
+            Self::Int64(v) => *v != 0,
+            Self::Double(v) => *v != 0.0,
+            Self::Decimal128(v) => !decimal128_is_zero(v),
+            Self::Null | Self::Undefined => false,
+            _ => true,
+        }
+    }
+
+    /// Returns true if this value is falsy in MongoDB aggregation expressions.
+    pub fn is_falsy(&self) -> bool {
+        !self.is_truthy()
+    }
+
+    /// Returns an estimate of the encoded length of this value in _O(1)_ time, not including the
+    /// type byte or key.
+    ///
+    /// The estimate is exact for all values except parsed documents and arrays and code with scope
+    /// that is not encoded. Parsed documents are exact if their length has been computed since they
+    /// were last mutated, for instance by serializing them; otherwise the length of parsed
+    /// documents, arrays and non-encoded scope documents is estimated from the number of fields or
+    /// elements, without visiting them.
+    pub fn estimate_bson_size(&self) -> usize {
+        match self {
+            Self::Document(MutableDocument::Owned(d)) => estimate_document_len(d),
+            Self::Array(MutableArray::Owned(a)) => 5 + a.len() * ESTIMATED_FIELD_LEN,
+            Self::JavaScriptCodeWithScope(MutableJavaScriptCodeWithScope::Owned(v)) => {
+                4 + raw_str_len(&v.code) + 5 + v.scope.len() * ESTIMATED_FIELD_LEN
+            }
+            Self::JavaScriptCodeWithScope(MutableJavaScriptCodeWithScope::Parsed {
+                code,
+                scope,
+            }) => 4 + raw_str_len(code) + estimate_document_len(scope),
+            v => v.raw_len(),
+        }
+    }
+
     /// Returns the raw binary coded length of this value.
     fn raw_len(&self) -> usize {
         match self {
@@ -113,8 +374,7 @@ impl<'a> MutableValue<'a> {
             Self::DateTime(_) => 8,
             Self::Null => 0,
             Self::RegularExpression(v) => v.raw_len(),
-            // No visibility into DbPointer components. Could still be fixed, it's just annoying.
-            Self::DbPointer(_) => unimplemented!(),
+            Self::DbPointer(v) => v.raw_len(),
             Self::JavaScriptCode(v) => raw_str_len(v),
             Self::Symbol(v) => raw_str_len(v),
             Self::JavaScriptCodeWithScope(v) => v.raw_len(),
@@ -140,8 +400,7 @@ impl<'a> MutableValue<'a> {
             Self::DateTime(v) => buf.put_i64_le(v.timestamp_millis()),
             Self::Null => (),
             Self::RegularExpression(v) => v.put(buf)?,
-            // No visibility into DbPointer components. Could still be fixed, it's just annoying.
-            Self::DbPointer(_) => unimplemented!(),
+            Self::DbPointer(v) => v.put(buf),
             Self::JavaScriptCode(v) => put_raw_str(v, buf),
             Self::Symbol(v) => put_raw_str(v, buf),
             Self::JavaScriptCodeWithScope(v) => v.put(buf)?,
@@ -158,6 +417,44 @@ impl<'a> MutableValue<'a> {
         Ok(())
     }
 
+    /// Copy any borrowed data so that the value has a `'static` lifetime.
+    ///
+    /// Any encoded documents or arrays are parsed, which may fail with a raw BSON parsing error.
+    pub fn into_owned(self) -> Result<MutableValue<'static>, bson::raw::Error> {
+        Ok(match self {
+            Self::Double(v) => MutableValue::Double(v),
+            Self::String(v) => MutableValue::String(v.into_owned().into()),
+            Self::Document(v) => MutableValue::Document(v.into_owned()?),
+            Self::Array(v) => MutableValue::Array(v.into_owned()?),
+            Self::Binary(v) => MutableValue::Binary(v.into_binary().into()),
+            Self::Undefined => MutableValue::Undefined,
+            Self::ObjectId(v) => MutableValue::ObjectId(v),
+            Self::Boolean(v) => MutableValue::Boolean(v),
+            Self::DateTime(v) => MutableValue::DateTime(v),
+            Self::Null => MutableValue::Null,
+            Self::RegularExpression(v) => MutableValue::RegularExpression(v.into_regex().into()),
+            Self::DbPointer(v) => v.into_bson()?.into(),
+            Self::JavaScriptCode(v) => MutableValue::JavaScriptCode(v.into_owned().into()),
+            Self::Symbol(v) => MutableValue::Symbol(v.into_owned().into()),
+            Self::JavaScriptCodeWithScope(v) => {
+                MutableValue::JavaScriptCodeWithScope(v.into_owned()?)
+            }
+            Self::Int32(v) => MutableValue::Int32(v),
+            Self::Timestamp(v) => MutableValue::Timestamp(v),
+            Self::Int64(v) => MutableValue::Int64(v),
+            Self::Decimal128(v) => MutableValue::Decimal128(v),
+            Self::MinKey => MutableValue::MinKey,
+            Self::MaxKey => MutableValue::MaxKey,
+        })
+    }
+
+    /// Replace this value in place with the output of f, which takes the current value by move.
+    ///
+    /// The value is temporarily `Null` while f runs, so if f panics this value is left `Null`.
+    pub fn replace_with<F: FnOnce(MutableValue<'a>) -> MutableValue<'a>>(&mut self, f: F) {
+        *self = f(std::mem::replace(self, Self::Null));
+    }
+
     pub fn as_f64(&self) -> Option<f64> {
         match self {
             Self::Double(v) => Some(*v),
@@ -186,6 +483,14 @@ impl<'a> MutableValue<'a> {
         }
     }
 
+    /// Move the document out of this value, or return the value unchanged if it is not a document.
+    pub fn into_document(self) -> Result<MutableDocument<'a>, Self> {
+        match self {
+            Self::Document(v) => Ok(v),
+            v => Err(v),
+        }
+    }
+
     pub fn as_array(&self) -> Option<&MutableArray<'a>> {
         match self {
             Self::Array(v) => Some(v),
@@ -200,6 +505,14 @@ impl<'a> MutableValue<'a> {
         }
     }
 
+    /// Move the array out of this value, or return the value unchanged if it is not an array.
+    pub fn into_array(self) -> Result<MutableArray<'a>, Self> {
+        match self {
+            Self::Array(v) => Ok(v),
+            v => Err(v),
+        }
+    }
+
     pub fn as_binary(&self) -> Option<&MutableBinary<'a>> {
         match self {
             Self::Binary(v) => Some(v),
@@ -262,6 +575,76 @@ impl<'a> MutableValue<'a> {
             _ => None,
         }
     }
+
+    /// Returns the code if this value is `JavaScriptCode`, otherwise `None`.
+    pub fn as_javascript_code(&self) -> Option<&str> {
+        match self {
+            Self::JavaScriptCode(v) => Some(v.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Returns the symbol if this value is a `Symbol`, otherwise `None`.
+    pub fn as_symbol(&self) -> Option<&str> {
+        match self {
+            Self::Symbol(v) => Some(v.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a `Decimal128`, otherwise `None`.
+    pub fn as_decimal128(&self) -> Option<Decimal128> {
+        match self {
+            Self::Decimal128(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the code with scope if this value is `JavaScriptCodeWithScope`, otherwise `None`.
+    pub fn as_javascript_with_scope(&self) -> Option<&MutableJavaScriptCodeWithScope<'a>> {
+        match self {
+            Self::JavaScriptCodeWithScope(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the code with scope if this value is
+    /// `JavaScriptCodeWithScope`, otherwise `None`.
+    pub fn as_javascript_with_scope_mut(
+        &mut self,
+    ) -> Option<&mut MutableJavaScriptCodeWithScope<'a>> {
+        match self {
+            Self::JavaScriptCodeWithScope(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an `f64` if it is any numeric type other than `Decimal128`.
+    ///
+    /// `Int64` values with a magnitude greater than 2^53 may lose precision.
+    pub fn as_number_f64(&self) -> Option<f64> {
+        match self {
+            Self::Double(v) => Some(*v),
+            Self::Int32(v) => Some(f64::from(*v)),
+            Self::Int64(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as an `i64` if it is an integer, or a `Double` that holds an integral
+    /// value in the range of `i64`.
+    pub fn as_number_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int32(v) => Some(i64::from(*v)),
+            Self::Int64(v) => Some(*v),
+            Self::Double(v)
+                if v.fract() == 0.0 && *v >= i64::MIN as f64 && *v < i64::MAX as f64 =>
+            {
+                Some(*v as i64)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<RawBsonRef<'a>> for MutableValue<'a> {
@@ -278,7 +661,7 @@ impl<'a> From<RawBsonRef<'a>> for MutableValue<'a> {
             RawBsonRef::DateTime(v) => Self::DateTime(v),
             RawBsonRef::Null => Self::Null,
             RawBsonRef::RegularExpression(v) => Self::RegularExpression(v.into()),
-            RawBsonRef::DbPointer(v) => Self::DbPointer(v),
+            RawBsonRef::DbPointer(v) => Self::DbPointer(v.into()),
             RawBsonRef::JavaScriptCode(v) => Self::JavaScriptCode(v.into()),
             RawBsonRef::Symbol(v) => Self::Symbol(v.into()),
             RawBsonRef::JavaScriptCodeWithScope(v) => Self::JavaScriptCodeWithScope(v.into()),
@@ -312,7 +695,7 @@ impl From<Bson> for MutableValue<'_> {
             Bson::DateTime(v) => Self::DateTime(v),
             Bson::Null => Self::Null,
             Bson::RegularExpression(v) => Self::RegularExpression(v.into()),
-            Bson::DbPointer(_) => unimplemented!(),
+            Bson::DbPointer(v) => Self::DbPointer(v.into()),
             Bson::JavaScriptCode(v) => Self::JavaScriptCode(v.into()),
             Bson::Symbol(v) => Self::Symbol(v.into()),
             Bson::JavaScriptCodeWithScope(v) => Self::JavaScriptCodeWithScope(v.into()),
@@ -428,6 +811,176 @@ impl From<Decimal128> for MutableValue<'_> {
     }
 }
 
+/// Copies any borrowed data into a [`bson::Bson`].
+///
+/// May fail with a raw BSON parsing error if the value contains an encoded document or array.
+impl TryFrom<MutableValue<'_>> for Bson {
+    type Error = bson::raw::Error;
+
+    fn try_from(value: MutableValue<'_>) -> Result<Self, Self::Error> {
+        Ok(match value {
+            MutableValue::Double(v) => Self::Double(v),
+            MutableValue::String(v) => Self::String(v.into_owned()),
+            MutableValue::Document(v) => Self::Document(v.try_into_document()?),
+            MutableValue::Array(v) => Self::Array(v.try_into_vec()?),
+            MutableValue::Binary(v) => Self::Binary(v.into_binary()),
+            MutableValue::Undefined => Self::Undefined,
+            MutableValue::ObjectId(v) => Self::ObjectId(v),
+            MutableValue::Boolean(v) => Self::Boolean(v),
+            MutableValue::DateTime(v) => Self::DateTime(v),
+            MutableValue::Null => Self::Null,
+            MutableValue::RegularExpression(v) => Self::RegularExpression(v.into_regex()),
+            MutableValue::DbPointer(v) => v.into_bson()?,
+            MutableValue::JavaScriptCode(v) => Self::JavaScriptCode(v.into_owned()),
+            MutableValue::Symbol(v) => Self::Symbol(v.into_owned()),
+            MutableValue::JavaScriptCodeWithScope(v) => {
+                Self::JavaScriptCodeWithScope(v.try_into_code_with_scope()?)
+            }
+            MutableValue::Int32(v) => Self::Int32(v),
+            MutableValue::Timestamp(v) => Self::Timestamp(v),
+            MutableValue::Int64(v) => Self::Int64(v),
+            MutableValue::Decimal128(v) => Self::Decimal128(v),
+            MutableValue::MinKey => Self::MinKey,
+            MutableValue::MaxKey => Self::MaxKey,
+        })
+    }
+}
+
+/// Converts values that do not own any heap allocations into a [`bson::RawBsonRef`].
+///
+/// Fails and returns the input value if it contains owned data, like an owned `String` or a
+/// [`ParsedDocument`].
+impl<'a> TryFrom<MutableValue<'a>> for RawBsonRef<'a> {
+    type Error = MutableValue<'a>;
+
+    fn try_from(value: MutableValue<'a>) -> Result<Self, Self::Error> {
+        Ok(match value {
+            MutableValue::Double(v) => Self::Double(v),
+            MutableValue::String(Cow::Borrowed(v)) => Self::String(v),
+            MutableValue::Document(MutableDocument::Borrowed(v)) => Self::Document(v),
+            MutableValue::Array(MutableArray::Borrowed(v)) => Self::Array(v),
+            MutableValue::Binary(MutableBinary::Borrowed(v)) => Self::Binary(v),
+            MutableValue::Undefined => Self::Undefined,
+            MutableValue::ObjectId(v) => Self::ObjectId(v),
+            MutableValue::Boolean(v) => Self::Boolean(v),
+            MutableValue::DateTime(v) => Self::DateTime(v),
+            MutableValue::Null => Self::Null,
+            MutableValue::RegularExpression(MutableRegex::Borrowed(v)) => {
+                Self::RegularExpression(v)
+            }
+            MutableValue::DbPointer(MutableDbPointer::Borrowed(v)) => Self::DbPointer(v),
+            MutableValue::JavaScriptCode(Cow::Borrowed(v)) => Self::JavaScriptCode(v),
+            MutableValue::Symbol(Cow::Borrowed(v)) => Self::Symbol(v),
+            MutableValue::JavaScriptCodeWithScope(MutableJavaScriptCodeWithScope::Borrowed(v)) => {
+                Self::JavaScriptCodeWithScope(v)
+            }
+            MutableValue::Int32(v) => Self::Int32(v),
+            MutableValue::Timestamp(v) => Self::Timestamp(v),
+            MutableValue::Int64(v) => Self::Int64(v),
+            MutableValue::Decimal128(v) => Self::Decimal128(v),
+            MutableValue::MinKey => Self::MinKey,
+            MutableValue::MaxKey => Self::MaxKey,
+            v => return Err(v),
+        })
+    }
+}
+
+/// Error returned when converting a [`MutableValue`] to a Rust type that does not match the type
+/// of the value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WrongType {
+    pub expected: ElementType,
+    pub actual: ElementType,
+}
+
+impl std::fmt::Display for WrongType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {:?}, got {:?}", self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for WrongType {}
+
+macro_rules! impl_try_from_value {
+    ($t:ty, $variant:ident, $v:ident => $convert:expr) => {
+        impl TryFrom<MutableValue<'_>> for $t {
+            type Error = WrongType;
+
+            fn try_from(value: MutableValue<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    MutableValue::$variant($v) => Ok($convert),
+                    v => Err(WrongType {
+                        expected: ElementType::$variant,
+                        actual: v.element_type(),
+                    }),
+                }
+            }
+        }
+
+        impl TryFrom<&MutableValue<'_>> for $t {
+            type Error = WrongType;
+
+            fn try_from(value: &MutableValue<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    MutableValue::$variant($v) => {
+                        let $v = $v.clone();
+                        Ok($convert)
+                    }
+                    v => Err(WrongType {
+                        expected: ElementType::$variant,
+                        actual: v.element_type(),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_value!(f64, Double, v => v);
+impl_try_from_value!(i32, Int32, v => v);
+impl_try_from_value!(i64, Int64, v => v);
+impl_try_from_value!(bool, Boolean, v => v);
+impl_try_from_value!(String, String, v => v.into_owned());
+impl_try_from_value!(ObjectId, ObjectId, v => v);
+impl_try_from_value!(DateTime, DateTime, v => v);
+impl_try_from_value!(Timestamp, Timestamp, v => v);
+impl_try_from_value!(Decimal128, Decimal128, v => v);
+impl_try_from_value!(Regex, RegularExpression, v => v.into_regex());
+impl_try_from_value!(Binary, Binary, v => v.into_binary());
+
+/// Values are equal if they have the same type and equal contents.
+///
+/// `Double` values are compared bit-for-bit, as they would be if the encoded BSON were compared,
+/// so `NaN == NaN` but `0.0 != -0.0`.
+impl PartialEq for MutableValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Double(a), Self::Double(b)) => a.to_bits() == b.to_bits(),
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Document(a), Self::Document(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Binary(a), Self::Binary(b)) => a == b,
+            (Self::Undefined, Self::Undefined) => true,
+            (Self::ObjectId(a), Self::ObjectId(b)) => a == b,
+            (Self::Boolean(a), Self::Boolean(b)) => a == b,
+            (Self::DateTime(a), Self::DateTime(b)) => a == b,
+            (Self::Null, Self::Null) => true,
+            (Self::RegularExpression(a), Self::RegularExpression(b)) => a == b,
+            (Self::DbPointer(a), Self::DbPointer(b)) => a == b,
+            (Self::JavaScriptCode(a), Self::JavaScriptCode(b)) => a == b,
+            (Self::Symbol(a), Self::Symbol(b)) => a == b,
+            (Self::JavaScriptCodeWithScope(a), Self::JavaScriptCodeWithScope(b)) => a == b,
+            (Self::Int32(a), Self::Int32(b)) => a == b,
+            (Self::Timestamp(a), Self::Timestamp(b)) => a == b,
+            (Self::Int64(a), Self::Int64(b)) => a == b,
+            (Self::Decimal128(a), Self::Decimal128(b)) => a == b,
+            (Self::MinKey, Self::MinKey) => true,
+            (Self::MaxKey, Self::MaxKey) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Contains either an encoded BSON document or a [`ParsedDocument`] that has decoded all of the
 /// key and [`MutableValue`] pairs for fast access and to allow mutation.
 ///
@@ -466,305 +1019,2506 @@ impl<'a> MutableDocument<'a> {
         }
     }
 
-    fn raw_len(&self) -> usize {
+    /// Return the encoded bytes of the document if it has not been parsed.
+    ///
+    /// An unparsed document has not been modified, so these bytes may be forwarded without
+    /// serializing the document.
+    pub fn as_raw_bytes(&self) -> Option<&[u8]> {
         match self {
-            Self::Borrowed(e) => e.as_bytes().len(),
-            Self::Owned(p) => p.raw_len(),
+            Self::Borrowed(e) => Some(e.as_bytes()),
+            Self::Owned(_) => None,
         }
     }
 
-    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
+    /// Return the encoded length of the document in _O(1)_ time if it has not been parsed.
+    ///
+    /// Use [`Self::size_hint()`] to bound the length of a parsed document.
+    pub fn byte_len(&self) -> Option<usize> {
+        self.as_raw_bytes().map(<[u8]>::len)
+    }
+
+    /// Return a reference to the encoded document if it has not been parsed.
+    ///
+    /// This may be handed to consumers of `AsRef<[u8]>` or [`RawDocument`] without copying.
+    pub fn as_raw(&self) -> Option<RawDocumentRef<'a>> {
         match self {
-            Self::Borrowed(e) => {
-                buf.put_slice(e.as_bytes());
-                Ok(())
-            }
-            Self::Owned(p) => p.put(buf),
+            Self::Borrowed(e) => Some(RawDocumentRef(e)),
+            Self::Owned(_) => None,
         }
     }
 
-    /// Produce an encoded raw document.
-    pub fn to_vec(&self) -> Result<Vec<u8>, bson::ser::Error> {
-        // TODO: cache the raw length in all MutableValues where length computation is non-trivial.
-        // Leaving this uncache is a problem because we will call raw_len() twice on the root
-        // document: once to size the output buffer and once to emit the buffer, and this will be
-        // done for all mutable objects down the tree. The borrow checker should help us here as
-        // all mutable methods can ensure that the cached value is invalidated.
-        // This is easy for ParsedDocument, MutableArray::Owned would need a lot of work.
-        let len = self.raw_len();
-        if len >= 32 << 20 {
-            return Err(bson::ser::Error::Io(Arc::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Exceeded max document length",
-            ))));
-        }
-        let mut buf = Vec::with_capacity(len);
-        self.put(&mut buf).map(|_| buf)
+    /// Borrow bytes as an encoded document without parsing it.
+    ///
+    /// This only checks the document length and terminator; individual elements are validated as
+    /// they are accessed. May fail with a raw BSON parsing error.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, bson::raw::Error> {
+        RawDocument::from_bytes(bytes).map(Self::Borrowed)
     }
-}
 
-impl<'a> From<&'a RawDocument> for MutableDocument<'a> {
-    fn from(value: &'a RawDocument) -> Self {
-        Self::Borrowed(value)
+    /// Returns the number of fields in the document.
+    ///
+    /// Runs in _O(n)_ time for an encoded document and may fail with a raw BSON parsing error.
+    pub fn len(&self) -> Result<usize, bson::raw::Error> {
+        match self {
+            Self::Borrowed(e) => e.into_iter().try_fold(0, |n, v| v.map(|_| n + 1)),
+            Self::Owned(p) => Ok(p.len()),
+        }
     }
-}
 
-impl<'a> From<ParsedDocument<'a>> for MutableDocument<'a> {
-    fn from(value: ParsedDocument<'a>) -> Self {
-        Self::Owned(value)
+    /// Returns true if the document contains no fields.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            // An empty encoded document contains only the length and null terminator.
+            Self::Borrowed(e) => e.as_bytes().len() <= 5,
+            Self::Owned(p) => p.is_empty(),
+        }
     }
-}
 
-impl From<Document> for MutableDocument<'_> {
-    fn from(value: Document) -> Self {
-        Self::Owned(value.into())
+    /// Remove all fields from the document. An encoded document is replaced without parsing.
+    pub fn clear(&mut self) {
+        match self {
+            Self::Borrowed(_) => *self = Self::Owned(ParsedDocument::new()),
+            Self::Owned(p) => p.clear(),
+        }
     }
-}
 
-/// Contains either an encoded BSON array or a [`Vec`] of [`MutableValue`]s decoded from a raw array
-/// to allow fast access and mutation.
-///
-/// NB: while [bson::RawArray] provides indexed access, it does so by decoding from the beginning
-/// of the array so it is often unwise to use [bson::RawArray::get] and friends.
-#[derive(Clone, Debug)]
-pub enum MutableArray<'a> {
-    Borrowed(&'a RawArray),
-    Owned(Vec<MutableValue<'a>>),
-}
+    /// Returns true if the document contains key.
+    ///
+    /// An encoded document is scanned without parsing, but this runs in _O(n)_ time and may fail
+    /// with a raw BSON parsing error.
+    pub fn contains_key(&self, key: impl AsRef<str>) -> Result<bool, bson::raw::Error> {
+        match self {
+            Self::Borrowed(e) => Ok(e.get(key)?.is_some()),
+            Self::Owned(p) => Ok(p.contains_key(key)),
+        }
+    }
 
-impl<'a> MutableArray<'a> {
-    /// Try to convert the representation to a [`ParsedArray`] from a [`RawArray`](bson::RawArray)
-    /// if necessary.
+    /// Get the value for key.
     ///
-    /// May fail with a raw BSON parsing error.
-    pub fn try_into_parsed(self) -> Result<Self, bson::raw::Error> {
+    /// Values of an encoded document are decoded without allocating, but this runs in _O(n)_ time
+    /// and may fail with a raw BSON parsing error. There is no `Index` implementation because a
+    /// reference cannot be returned into an encoded document; index a [`ParsedDocument`] instead.
+    pub fn get(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<Cow<'_, MutableValue<'a>>>, bson::raw::Error> {
         match self {
-            Self::Owned(p) => Ok(p.into()),
-            Self::Borrowed(e) => Self::encoded_to_parsed(e).map(Self::from),
+            Self::Borrowed(e) => Ok(e.get(key)?.map(|v| Cow::Owned(v.into()))),
+            Self::Owned(p) => Ok(p.get(key).map(Cow::Borrowed)),
         }
     }
 
-    /// Try to convert the representation to a [`ParsedArray`] from a [`RawArray`](bson::RawArray)
-    /// if necessary and return a mutable reference to the `ParsedArray`.
+    /// Get a mutable reference to the value for key, parsing the document if necessary.
     ///
     /// May fail with a raw BSON parsing error.
-    pub fn to_parsed(&mut self) -> Result<&mut Vec<MutableValue<'a>>, bson::raw::Error> {
-        if let Self::Borrowed(e) = self {
-            *self = Self::Owned(Self::encoded_to_parsed(e as &RawArray)?);
-        }
-        match self {
-            Self::Borrowed(_) => unreachable!(),
-            Self::Owned(p) => Ok(p),
+    pub fn get_mut(
+        &mut self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<&mut MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.get_mut(key))
+    }
+
+    /// Insert a value for key and return the previous value if any, parsing the document if
+    /// necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn insert<V: Into<MutableValue<'static>>>(
+        &mut self,
+        key: impl Into<String>,
+        value: V,
+    ) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.insert(key, value))
+    }
+
+    /// Remove key and return the value for that key if present, parsing the document if
+    /// necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn remove(
+        &mut self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.remove(key))
+    }
+
+    /// Iterate over the entries of the document without parsing it.
+    ///
+    /// Entries of an encoded document are decoded as they are visited; parsed entries are yielded
+    /// by reference. Yields a raw BSON parsing error if the document is malformed.
+    pub fn iter(&self) -> MutableDocumentIter<'_, 'a> {
+        MutableDocumentIter(match self {
+            Self::Borrowed(e) => MutableDocumentIterInner::Borrowed(e.iter_elements()),
+            Self::Owned(p) => MutableDocumentIterInner::Owned(p.fields_iter()),
+        })
+    }
+
+    /// Iterate over the keys and value types of the document without parsing it.
+    ///
+    /// Values of an encoded document are not decoded, so this is cheaper than [`Self::iter`].
+    /// Yields a raw BSON parsing error if the document is malformed.
+    pub fn field_types(
+        &self,
+    ) -> impl Iterator<Item = Result<(&str, ElementType), bson::raw::Error>> {
+        let (borrowed, owned) = match self {
+            Self::Borrowed(e) => (Some(e.iter_elements()), None),
+            Self::Owned(p) => (None, Some(p.field_types())),
+        };
+        borrowed
+            .into_iter()
+            .flatten()
+            .map(|e| e.map(|e| (e.key(), e.element_type())))
+            .chain(owned.into_iter().flatten().map(Ok))
+    }
+
+    /// Count the fields of the document by [`ElementType`], in the order each type first appears.
+    ///
+    /// Values of an encoded document are not decoded. Counts are returned as a list of pairs for
+    /// the same reason as [`MutableArray::group_by_type`]. May fail with a raw BSON parsing error.
+    pub fn count_by_type(&self) -> Result<Vec<(ElementType, usize)>, bson::raw::Error> {
+        let mut counts = vec![];
+        for field in self.field_types() {
+            count_type(&mut counts, field?.1);
         }
+        Ok(counts)
     }
 
-    fn encoded_to_parsed(raw: &RawArray) -> Result<Vec<MutableValue<'_>>, bson::raw::Error> {
-        let mut values = vec![];
-        for e in raw.into_iter() {
-            values.push((e?).into());
+    /// Count the fields of the document by [`ElementType`] like [`Self::count_by_type`], including
+    /// all values nested in embedded documents and arrays.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn count_by_type_recursive(&self) -> Result<Vec<(ElementType, usize)>, bson::raw::Error> {
+        let mut counts = vec![];
+        for field in self.iter() {
+            count_types_recursive(&mut counts, &field?.1)?;
         }
-        Ok(values)
+        Ok(counts)
     }
 
-    fn raw_len(&self) -> usize {
+    /// Iterate over the entries of the document with mutable values, parsing the document if
+    /// necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn iter_mut(
+        &mut self,
+    ) -> Result<impl Iterator<Item = (&str, &mut MutableValue<'a>)>, bson::raw::Error> {
+        Ok(self.to_parsed()?.iter_mut())
+    }
+
+    /// Returns a [`ParsedDocument`] view of this document, parsing it if necessary.
+    fn as_parsed(&self) -> Result<Cow<'_, ParsedDocument<'a>>, bson::raw::Error> {
         match self {
-            Self::Borrowed(e) => e.as_bytes().len(),
-            Self::Owned(p) => {
-                // TODO: more efficient way of counting key bytes.
-                p.iter()
-                    .enumerate()
-                    .map(|(i, v)| 1 + raw_cstr_len(itoa::Buffer::new().format(i)) + v.raw_len())
-                    .sum::<usize>()
-                    // doc length
-                    + 4usize
-                    // doc null terminator
-                    + 1usize
-            }
+            Self::Borrowed(e) => ParsedDocument::try_from(*e).map(Cow::Owned),
+            Self::Owned(p) => Ok(Cow::Borrowed(p)),
         }
     }
 
-    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
-        match self {
+    /// Copy any borrowed data so that the document has a `'static` lifetime.
+    ///
+    /// An encoded document is parsed, which may fail with a raw BSON parsing error.
+    pub fn into_owned(self) -> Result<MutableDocument<'static>, bson::raw::Error> {
+        let parsed = match self {
+            Self::Borrowed(e) => ParsedDocument::try_from(e)?,
+            Self::Owned(p) => p,
+        };
+        parsed.into_owned().map(MutableDocument::Owned)
+    }
+
+    /// Copy this document and all nested values so that the copy has a `'static` lifetime.
+    ///
+    /// Unlike [`Clone`], which preserves any borrowed data, every nested document and array is
+    /// parsed and copied. Encoded documents are not validated until they are parsed, so this may
+    /// fail with a raw BSON parsing error.
+    pub fn deep_clone_static(&self) -> Result<MutableDocument<'static>, bson::raw::Error> {
+        self.clone().into_owned()
+    }
+
+    /// Copy the contents into a [`bson::Document`].
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn try_into_document(self) -> Result<Document, bson::raw::Error> {
+        match self {
+            Self::Borrowed(e) => Document::try_from(e),
+            Self::Owned(p) => p.try_into_document(),
+        }
+    }
+
+    /// Returns lower and upper bounds on the encoded length of the document in bytes in _O(1)_
+    /// time, like [`Iterator::size_hint()`].
+    ///
+    /// The bounds are exact for encoded documents and for parsed documents whose length has been
+    /// computed since they were last mutated, for instance by serializing them. Otherwise the
+    /// lower bound assumes the smallest possible encoding of each field and there is no upper
+    /// bound.
+    pub fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Borrowed(e) => (e.as_bytes().len(), Some(e.as_bytes().len())),
+            Self::Owned(p) => match p.cached_raw_len() {
+                Some(len) => (len, Some(len)),
+                // 1 byte for type and 1 byte for an empty key per field.
+                None => (5 + p.len() * 2, None),
+            },
+        }
+    }
+
+    fn raw_len(&self) -> usize {
+        match self {
+            Self::Borrowed(e) => e.as_bytes().len(),
+            Self::Owned(p) => p.raw_len(),
+        }
+    }
+
+    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
+        match self {
             Self::Borrowed(e) => {
                 buf.put_slice(e.as_bytes());
                 Ok(())
             }
-            Self::Owned(p) => {
-                buf.put_i32_le(
-                    self.raw_len()
-                        .try_into()
-                        .expect("message len validated before put"),
-                );
-                for (i, v) in p.iter().enumerate() {
-                    buf.put_u8(v.element_type() as u8);
-                    put_raw_cstr(itoa::Buffer::new().format(i), buf)
-                        .expect("itoa does not contain nulls");
-                    v.put(buf)?;
-                }
-                buf.put_u8(0);
-                Ok(())
+            Self::Owned(p) => p.put(buf),
+        }
+    }
+
+    /// Produce an encoded raw document.
+    pub fn to_vec(&self) -> Result<Vec<u8>, bson::ser::Error> {
+        self.to_vec_with_options(&SerializeOptions::default())
+    }
+
+    /// Produce an encoded raw document, failing if it exceeds the limits in options.
+    pub fn to_vec_with_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<Vec<u8>, bson::ser::Error> {
+        nesting::check_serialize_nesting(|d| self.validate_nesting(d), options)?;
+        encode_to_vec(self.raw_len(), options, |buf| self.put(buf))
+    }
+}
+
+/// Iterates over the entries of the document in order.
+///
+/// Entries of an encoded document are decoded lazily without allocating, so the iterator yields
+/// a raw BSON parsing error if the document is malformed.
+impl<'a> IntoIterator for MutableDocument<'a> {
+    type Item = Result<(Cow<'a, str>, MutableValue<'a>), bson::raw::Error>;
+    type IntoIter = MutableDocumentIntoIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MutableDocumentIntoIter(match self {
+            Self::Borrowed(e) => MutableDocumentIntoIterInner::Borrowed(e.iter_elements()),
+            Self::Owned(p) => MutableDocumentIntoIterInner::Owned(p.into_iter()),
+        })
+    }
+}
+
+/// An owning iterator over the entries of a [`MutableDocument`] in order.
+pub struct MutableDocumentIntoIter<'a>(MutableDocumentIntoIterInner<'a>);
+
+enum MutableDocumentIntoIterInner<'a> {
+    Borrowed(RawIter<'a>),
+    Owned(ParsedDocumentIntoIter<'a>),
+}
+
+impl<'a> Iterator for MutableDocumentIntoIter<'a> {
+    type Item = Result<(Cow<'a, str>, MutableValue<'a>), bson::raw::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            MutableDocumentIntoIterInner::Borrowed(it) => it.next().map(|e| {
+                let e = e?;
+                Ok((e.key().into(), e.value()?.into()))
+            }),
+            MutableDocumentIntoIterInner::Owned(it) => it.next().map(Ok),
+        }
+    }
+}
+
+/// A borrowing iterator over the entries of a [`MutableDocument`] in order.
+///
+/// Entries of an encoded document are decoded as they are visited without parsing the whole
+/// document.
+pub struct MutableDocumentIter<'i, 'a>(MutableDocumentIterInner<'i, 'a>);
+
+enum MutableDocumentIterInner<'i, 'a> {
+    Borrowed(RawIter<'a>),
+    Owned(indexmap::map::Iter<'i, Cow<'a, str>, MutableValue<'a>>),
+}
+
+impl<'i, 'a> Iterator for MutableDocumentIter<'i, 'a> {
+    type Item = Result<(&'i str, Cow<'i, MutableValue<'a>>), bson::raw::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            MutableDocumentIterInner::Borrowed(it) => it.next().map(|e| {
+                let e = e?;
+                Ok((e.key(), Cow::Owned(e.value()?.into())))
+            }),
+            MutableDocumentIterInner::Owned(it) => {
+                it.next().map(|(k, v)| Ok((k.as_ref(), Cow::Borrowed(v))))
             }
         }
     }
 }
 
-impl<'a> From<&'a RawArray> for MutableArray<'a> {
-    fn from(value: &'a RawArray) -> Self {
-        Self::Borrowed(value)
+/// Iterate over the entries of the document by reference. Yields a raw BSON parsing error if the
+/// document is malformed.
+impl<'i, 'a> IntoIterator for &'i MutableDocument<'a> {
+    type Item = Result<(&'i str, Cow<'i, MutableValue<'a>>), bson::raw::Error>;
+    type IntoIter = MutableDocumentIter<'i, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
-impl<'a> From<Vec<MutableValue<'a>>> for MutableArray<'a> {
-    fn from(value: Vec<MutableValue<'a>>) -> Self {
-        Self::Owned(value)
+/// Documents are equal if they contain the same keys in the same order with equal values.
+///
+/// Equality is sensitive to field order: `{a: 1, b: 2}` and `{b: 2, a: 1}` are not equal, matching
+/// the BSON comparison rules used by MongoDB.
+///
+/// Two borrowed documents are compared by their encoded bytes, so identical malformed encodings
+/// are equal. Two owned documents are compared field by field. When comparing a borrowed document
+/// to an owned document the owned document is encoded and compared to the borrowed bytes; since
+/// encoding is canonical this is equivalent to comparing values, and a malformed borrowed document
+/// is never equal to an owned document.
+impl PartialEq for MutableDocument<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Borrowed(a), Self::Borrowed(b)) => a.as_bytes() == b.as_bytes(),
+            (Self::Owned(a), Self::Owned(b)) => a == b,
+            (Self::Borrowed(r), Self::Owned(p)) | (Self::Owned(p), Self::Borrowed(r)) => {
+                p.raw_len() == r.as_bytes().len()
+                    && p.to_vec_with_options(&SerializeOptions {
+                        max_document_size: None,
+                        max_nesting_depth: None,
+                    })
+                    .is_ok_and(|p| p == r.as_bytes())
+            }
+        }
     }
 }
 
-impl From<Vec<Bson>> for MutableArray<'_> {
-    fn from(value: Vec<Bson>) -> Self {
-        Self::Owned(value.into_iter().map(MutableValue::from).collect())
+/// A reference to an unmodified encoded document, returned by [`MutableDocument::as_raw`].
+#[derive(Clone, Copy, Debug)]
+pub struct RawDocumentRef<'a>(&'a RawDocument);
+
+impl<'a> RawDocumentRef<'a> {
+    /// Returns the encoded bytes of the document.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0.as_bytes()
+    }
+
+    /// Returns the underlying raw document.
+    pub fn as_raw_document(&self) -> &'a RawDocument {
+        self.0
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum MutableBinary<'a> {
-    Borrowed(RawBinaryRef<'a>),
-    Owned(Binary),
+impl AsRef<[u8]> for RawDocumentRef<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
 }
 
-impl MutableBinary<'_> {
-    fn raw_len(&self) -> usize {
-        let bytes = match self {
-            Self::Borrowed(v) => v.bytes,
-            Self::Owned(v) => v.bytes.as_ref(),
-        };
-        // length of the byte string + 4 bytes for length + 1 byte for subtype.
-        4 + bytes.len() + 1
+impl AsRef<RawDocument> for RawDocumentRef<'_> {
+    fn as_ref(&self) -> &RawDocument {
+        self.0
     }
+}
 
-    fn put(&self, buf: &mut impl BufMut) {
-        let (bytes, subtype) = match self {
-            Self::Borrowed(v) => (v.bytes, v.subtype),
-            Self::Owned(v) => (v.bytes.as_ref(), v.subtype),
-        };
-        buf.put_i32_le(
-            bytes
-                .len()
-                .try_into()
-                .expect("message len verified before put"),
-        );
-        buf.put_u8(subtype.into());
-        buf.put_slice(bytes);
+/// The default document is an empty [`ParsedDocument`].
+impl Default for MutableDocument<'_> {
+    fn default() -> Self {
+        Self::Owned(ParsedDocument::new())
     }
 }
 
-impl<'a> From<RawBinaryRef<'a>> for MutableBinary<'a> {
-    fn from(value: RawBinaryRef<'a>) -> Self {
+impl<'a> From<&'a RawDocument> for MutableDocument<'a> {
+    fn from(value: &'a RawDocument) -> Self {
         Self::Borrowed(value)
     }
 }
 
-impl From<Binary> for MutableBinary<'_> {
-    fn from(value: Binary) -> Self {
+impl<'a> From<ParsedDocument<'a>> for MutableDocument<'a> {
+    fn from(value: ParsedDocument<'a>) -> Self {
         Self::Owned(value)
     }
 }
 
+impl From<Document> for MutableDocument<'_> {
+    fn from(value: Document) -> Self {
+        Self::Owned(value.into())
+    }
+}
+
+/// Contains either an encoded BSON array or a [`Vec`] of [`MutableValue`]s decoded from a raw array
+/// to allow fast access and mutation.
+///
+/// NB: while [bson::RawArray] provides indexed access, it does so by decoding from the beginning
+/// of the array so it is often unwise to use [bson::RawArray::get] and friends.
 #[derive(Clone, Debug)]
-pub enum MutableRegex<'a> {
-    Borrowed(RawRegexRef<'a>),
-    Owned(Regex),
+pub enum MutableArray<'a> {
+    Borrowed(&'a RawArray),
+    Owned(Vec<MutableValue<'a>>),
 }
 
-impl MutableRegex<'_> {
-    fn raw_len(&self) -> usize {
-        let (pattern, options) = self.parts();
-        raw_cstr_len(pattern) + raw_cstr_len(options)
+impl<'a> MutableArray<'a> {
+    /// Create an empty parsed array with space for at least capacity elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::Owned(Vec::with_capacity(capacity))
     }
 
-    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
-        let (pattern, options) = self.parts();
-        put_raw_cstr(pattern, buf)?;
-        put_raw_cstr(options, buf)
+    /// Try to convert the representation to a [`ParsedArray`] from a [`RawArray`](bson::RawArray)
+    /// if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn try_into_parsed(self) -> Result<Self, bson::raw::Error> {
+        match self {
+            Self::Owned(p) => Ok(p.into()),
+            Self::Borrowed(e) => Self::encoded_to_parsed(e).map(Self::from),
+        }
     }
 
-    fn parts(&self) -> (&str, &str) {
+    /// Try to convert the representation to a [`ParsedArray`] from a [`RawArray`](bson::RawArray)
+    /// if necessary and return a mutable reference to the `ParsedArray`.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn to_parsed(&mut self) -> Result<&mut Vec<MutableValue<'a>>, bson::raw::Error> {
+        if let Self::Borrowed(e) = self {
+            *self = Self::Owned(Self::encoded_to_parsed(e as &RawArray)?);
+        }
         match self {
-            Self::Borrowed(v) => (v.pattern, v.options),
-            Self::Owned(v) => (v.pattern.as_ref(), v.options.as_ref()),
+            Self::Borrowed(_) => unreachable!(),
+            Self::Owned(p) => Ok(p),
         }
     }
-}
 
-impl<'a> From<RawRegexRef<'a>> for MutableRegex<'a> {
-    fn from(value: RawRegexRef<'a>) -> Self {
-        Self::Borrowed(value)
+    /// Return the encoded bytes of the array if it has not been parsed.
+    ///
+    /// An unparsed array has not been modified, so these bytes may be forwarded without
+    /// serializing the array.
+    pub fn as_raw_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Borrowed(e) => Some(e.as_bytes()),
+            Self::Owned(_) => None,
+        }
     }
-}
 
-impl From<Regex> for MutableRegex<'_> {
-    fn from(value: Regex) -> Self {
-        Self::Owned(value)
+    /// Returns the number of elements in the array.
+    ///
+    /// Runs in _O(n)_ time for an encoded array and may fail with a raw BSON parsing error.
+    pub fn len(&self) -> Result<usize, bson::raw::Error> {
+        match self {
+            Self::Borrowed(e) => e.into_iter().try_fold(0, |n, v| v.map(|_| n + 1)),
+            Self::Owned(p) => Ok(p.len()),
+        }
     }
-}
 
-#[derive(Clone, Debug)]
-pub enum MutableJavaScriptCodeWithScope<'a> {
-    Borrowed(RawJavaScriptCodeWithScopeRef<'a>),
-    Owned(JavaScriptCodeWithScope),
-}
+    /// Returns true if the array contains no elements.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            // An empty encoded array contains only the length and null terminator.
+            Self::Borrowed(e) => e.as_bytes().len() <= 5,
+            Self::Owned(p) => p.is_empty(),
+        }
+    }
 
-impl MutableJavaScriptCodeWithScope<'_> {
-    fn raw_len(&self) -> usize {
+    /// Remove all elements from the array. An encoded array is replaced without parsing.
+    pub fn clear(&mut self) {
         match self {
-            Self::Borrowed(v) => 4 + raw_str_len(v.code) + v.scope.as_bytes().len(),
-            Self::Owned(v) => {
-                // TODO: the owned object should be code + ParsedDocument scoped.
-                4 + raw_str_len(&v.code) + bson::to_vec(&v.scope).unwrap().len()
-            }
+            Self::Borrowed(_) => *self = Self::Owned(vec![]),
+            Self::Owned(p) => p.clear(),
         }
     }
 
-    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
+    /// Get the element at index.
+    ///
+    /// Elements of an encoded array are decoded without allocating, but this runs in _O(n)_ time
+    /// and may fail with a raw BSON parsing error. Like [`MutableDocument::get`] there is no
+    /// `Index` implementation; index the `Vec` returned by [`Self::to_parsed`] instead.
+    pub fn get(&self, index: usize) -> Result<Option<Cow<'_, MutableValue<'a>>>, bson::raw::Error> {
         match self {
-            Self::Borrowed(v) => {
-                buf.put_i32_le(
-                    (raw_str_len(v.code) + v.scope.as_bytes().len() + 4)
-                        .try_into()
-                        .expect("document length verified before put()"),
-                );
-                put_raw_str(v.code, buf);
-                buf.put_slice(v.scope.as_bytes());
-            }
-            Self::Owned(v) => {
-                let encoded_scope = bson::to_vec(&v.scope)?;
-                buf.put_i32_le(
-                    (raw_str_len(&v.code) + encoded_scope.len() + 4)
-                        .try_into()
-                        .expect("document length verified before put()"),
-                );
-                put_raw_str(&v.code, buf);
-                buf.put_slice(&encoded_scope);
+            Self::Borrowed(e) => Ok(e.get(index)?.map(|v| Cow::Owned(v.into()))),
+            Self::Owned(p) => Ok(p.get(index).map(Cow::Borrowed)),
+        }
+    }
+
+    /// Iterate over the elements of the array without parsing it.
+    ///
+    /// Elements of an encoded array are decoded as they are visited; parsed elements are yielded
+    /// by reference. Yields a raw BSON parsing error if the array is malformed.
+    pub fn iter(&self) -> MutableArrayIter<'_, 'a> {
+        MutableArrayIter(match self {
+            Self::Borrowed(e) => MutableArrayIterInner::Borrowed(e.into_iter()),
+            Self::Owned(p) => MutableArrayIterInner::Owned(p.iter()),
+        })
+    }
+
+    /// Returns true if the array contains an element equal to value.
+    ///
+    /// Encoded arrays are scanned without parsing. May fail with a raw BSON parsing error.
+    pub fn contains(&self, value: &MutableValue<'_>) -> Result<bool, bson::raw::Error> {
+        self.position(value).map(|p| p.is_some())
+    }
+
+    /// Returns the index of the first element equal to value.
+    ///
+    /// Encoded arrays are scanned without parsing. May fail with a raw BSON parsing error.
+    pub fn position(&self, value: &MutableValue<'_>) -> Result<Option<usize>, bson::raw::Error> {
+        for (i, v) in self.iter().enumerate() {
+            if *v? == *value {
+                return Ok(Some(i));
             }
-        };
-        Ok(())
+        }
+        Ok(None)
     }
-}
 
-impl<'a> From<RawJavaScriptCodeWithScopeRef<'a>> for MutableJavaScriptCodeWithScope<'a> {
-    fn from(value: RawJavaScriptCodeWithScopeRef<'a>) -> Self {
-        Self::Borrowed(value)
+    /// Get the first element of the array.
+    ///
+    /// The first element of an encoded array is decoded without scanning the rest of the array.
+    /// May fail with a raw BSON parsing error.
+    pub fn first(&self) -> Result<Option<Cow<'_, MutableValue<'a>>>, bson::raw::Error> {
+        self.iter().next().transpose()
     }
-}
 
-impl From<JavaScriptCodeWithScope> for MutableJavaScriptCodeWithScope<'_> {
-    fn from(value: JavaScriptCodeWithScope) -> Self {
-        Self::Owned(value)
+    /// Get the last element of the array.
+    ///
+    /// Runs in _O(n)_ time for an encoded array and may fail with a raw BSON parsing error.
+    pub fn last(&self) -> Result<Option<Cow<'_, MutableValue<'a>>>, bson::raw::Error> {
+        match self {
+            Self::Borrowed(_) => self.iter().try_fold(None, |_, v| v.map(Some)),
+            Self::Owned(p) => Ok(p.last().map(Cow::Borrowed)),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {}
+    /// Get a mutable reference to the first element, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn first_mut(&mut self) -> Result<Option<&mut MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.first_mut())
+    }
+
+    /// Get a mutable reference to the last element, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn last_mut(&mut self) -> Result<Option<&mut MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.last_mut())
+    }
+
+    /// Get a mutable reference to the element at index, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn get_mut(
+        &mut self,
+        index: usize,
+    ) -> Result<Option<&mut MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.get_mut(index))
+    }
+
+    /// Append a value to the array, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn push<V: Into<MutableValue<'static>>>(
+        &mut self,
+        value: V,
+    ) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.push(value.into());
+        Ok(())
+    }
+
+    /// Append all values from iter to the array, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn extend<I: IntoIterator<Item = MutableValue<'a>>>(
+        &mut self,
+        iter: I,
+    ) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.extend(iter);
+        Ok(())
+    }
+
+    /// Move all elements of other to the end of this array, leaving other empty. Both arrays are
+    /// parsed if necessary.
+    ///
+    /// May fail with a raw BSON parsing error, in which case neither array is modified.
+    pub fn append(&mut self, other: &mut MutableArray<'a>) -> Result<(), bson::raw::Error> {
+        let values = other.to_parsed()?;
+        self.to_parsed()?.append(values);
+        Ok(())
+    }
+
+    /// Remove the elements in range and return them as an iterator, parsing the array if
+    /// necessary.
+    ///
+    /// Panics if range is out of bounds, like [`Vec::drain()`]. May fail with a raw BSON parsing
+    /// error.
+    pub fn drain(
+        &mut self,
+        range: impl RangeBounds<usize>,
+    ) -> Result<impl Iterator<Item = MutableValue<'a>> + '_, bson::raw::Error> {
+        Ok(self.to_parsed()?.drain(range))
+    }
+
+    /// Split the array in two at index at, returning the elements from at onward and leaving the
+    /// elements before at in this array. The array is parsed if necessary.
+    ///
+    /// Panics if at is greater than the length, like [`Vec::split_off()`]. May fail with a raw
+    /// BSON parsing error.
+    pub fn split_off(&mut self, at: usize) -> Result<MutableArray<'a>, bson::raw::Error> {
+        Ok(Self::Owned(self.to_parsed()?.split_off(at)))
+    }
+
+    /// Remove and return the last element of the array, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn pop(&mut self) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        Ok(self.to_parsed()?.pop())
+    }
+
+    /// Remove and return the element at index, parsing the array if necessary. All elements after
+    /// index are shifted down.
+    ///
+    /// Returns `None` if index is out of bounds. May fail with a raw BSON parsing error.
+    pub fn remove(&mut self, index: usize) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        let values = self.to_parsed()?;
+        Ok((index < values.len()).then(|| values.remove(index)))
+    }
+
+    /// Retain only the elements for which f returns true, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn retain<F: FnMut(&mut MutableValue<'a>) -> bool>(
+        &mut self,
+        f: F,
+    ) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.retain_mut(f);
+        Ok(())
+    }
+
+    /// Group the elements of the array by [`ElementType`], with groups in the order their type
+    /// first appears and elements within each group in array order.
+    ///
+    /// `ElementType` does not implement `Hash` or `Ord` so groups are returned as a list of pairs.
+    /// May fail with a raw BSON parsing error.
+    pub fn group_by_type(
+        &self,
+    ) -> Result<Vec<(ElementType, Vec<MutableValue<'a>>)>, bson::raw::Error> {
+        let mut groups: Vec<(ElementType, Vec<MutableValue<'a>>)> = vec![];
+        for v in self.iter() {
+            let v = v?;
+            let element_type = v.element_type();
+            match groups.iter_mut().find(|(t, _)| *t == element_type) {
+                Some((_, group)) => group.push(v.into_owned()),
+                None => groups.push((element_type, vec![v.into_owned()])),
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Replace each element with the output of f, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn map_into<F: FnMut(MutableValue<'a>) -> MutableValue<'a>>(
+        &mut self,
+        mut f: F,
+    ) -> Result<(), bson::raw::Error> {
+        for v in self.to_parsed()? {
+            v.replace_with(&mut f);
+        }
+        Ok(())
+    }
+
+    /// Remove consecutive duplicate elements, parsing the array if necessary.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn dedup(&mut self) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.dedup();
+        Ok(())
+    }
+
+    /// Sort the array with a comparator function, preserving the order of equal elements.
+    ///
+    /// [`compare_values()`] sorts using the MongoDB canonical BSON sort order. May fail with a
+    /// raw BSON parsing error.
+    pub fn sort_by<F: FnMut(&MutableValue<'a>, &MutableValue<'a>) -> std::cmp::Ordering>(
+        &mut self,
+        compare: F,
+    ) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.sort_by(compare);
+        Ok(())
+    }
+
+    /// Sort the array with a comparator function without preserving the order of equal elements.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn sort_unstable_by<
+        F: FnMut(&MutableValue<'a>, &MutableValue<'a>) -> std::cmp::Ordering,
+    >(
+        &mut self,
+        compare: F,
+    ) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.sort_unstable_by(compare);
+        Ok(())
+    }
+
+    /// Sort the array with a key extraction function, preserving the order of equal elements.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn sort_by_key<K: Ord, F: FnMut(&MutableValue<'a>) -> K>(
+        &mut self,
+        f: F,
+    ) -> Result<(), bson::raw::Error> {
+        self.to_parsed()?.sort_by_key(f);
+        Ok(())
+    }
+
+    /// Copy any borrowed data so that the array has a `'static` lifetime.
+    ///
+    /// An encoded array is parsed, which may fail with a raw BSON parsing error.
+    pub fn into_owned(self) -> Result<MutableArray<'static>, bson::raw::Error> {
+        self.into_iter()
+            .map(|v| v?.into_owned())
+            .collect::<Result<Vec<_>, _>>()
+            .map(MutableArray::Owned)
+    }
+
+    /// Copy the contents into a [`Vec`] of [`bson::Bson`] values.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn try_into_vec(self) -> Result<Vec<Bson>, bson::raw::Error> {
+        match self {
+            Self::Borrowed(e) => Vec::<Bson>::try_from(e),
+            Self::Owned(p) => p.into_iter().map(Bson::try_from).collect(),
+        }
+    }
+
+    fn encoded_to_parsed(raw: &RawArray) -> Result<Vec<MutableValue<'_>>, bson::raw::Error> {
+        let mut values = vec![];
+        for e in raw.into_iter() {
+            values.push((e?).into());
+        }
+        Ok(values)
+    }
+
+    /// Returns the values of this array, parsing them if necessary.
+    fn as_parsed(&self) -> Result<Cow<'_, [MutableValue<'a>]>, bson::raw::Error> {
+        match self {
+            Self::Borrowed(e) => Self::encoded_to_parsed(e).map(Cow::Owned),
+            Self::Owned(p) => Ok(Cow::Borrowed(p.as_slice())),
+        }
+    }
+
+    /// Returns lower and upper bounds on the encoded length of the array in bytes in _O(1)_
+    /// time, like [`Iterator::size_hint()`].
+    ///
+    /// The bounds are exact for encoded arrays. Parsed arrays have a lower bound that assumes the
+    /// smallest possible encoding of each element and no upper bound.
+    pub fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Borrowed(e) => (e.as_bytes().len(), Some(e.as_bytes().len())),
+            // 1 byte for type and 2 bytes for the shortest index key per element.
+            Self::Owned(p) => (5 + p.len() * 3, None),
+        }
+    }
+
+    fn raw_len(&self) -> usize {
+        match self {
+            Self::Borrowed(e) => e.as_bytes().len(),
+            Self::Owned(p) => {
+                // TODO: more efficient way of counting key bytes.
+                p.iter()
+                    .enumerate()
+                    .map(|(i, v)| 1 + raw_cstr_len(itoa::Buffer::new().format(i)) + v.raw_len())
+                    .sum::<usize>()
+                    // doc length
+                    + 4usize
+                    // doc null terminator
+                    + 1usize
+            }
+        }
+    }
+
+    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
+        match self {
+            Self::Borrowed(e) => {
+                buf.put_slice(e.as_bytes());
+                Ok(())
+            }
+            Self::Owned(p) => {
+                buf.put_i32_le(
+                    self.raw_len()
+                        .try_into()
+                        .expect("message len validated before put"),
+                );
+                for (i, v) in p.iter().enumerate() {
+                    buf.put_u8(v.element_type() as u8);
+                    put_raw_cstr(itoa::Buffer::new().format(i), buf)
+                        .expect("itoa does not contain nulls");
+                    v.put(buf)?;
+                }
+                buf.put_u8(0);
+                Ok(())
+            }
+        }
+    }
+
+    /// Produce an encoded raw array.
+    pub fn to_vec(&self) -> Result<Vec<u8>, bson::ser::Error> {
+        encode_to_vec(self.raw_len(), &SerializeOptions::default(), |buf| {
+            self.put(buf)
+        })
+    }
+}
+
+/// Iterates over the elements of the array in order.
+///
+/// Elements of an encoded array are decoded lazily without allocating, so the iterator yields a
+/// raw BSON parsing error if the array is malformed.
+impl<'a> IntoIterator for MutableArray<'a> {
+    type Item = Result<MutableValue<'a>, bson::raw::Error>;
+    type IntoIter = MutableArrayIntoIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MutableArrayIntoIter(match self {
+            Self::Borrowed(e) => MutableArrayIntoIterInner::Borrowed(e.into_iter()),
+            Self::Owned(p) => MutableArrayIntoIterInner::Owned(p.into_iter()),
+        })
+    }
+}
+
+/// An owning iterator over the elements of a [`MutableArray`] in order.
+pub struct MutableArrayIntoIter<'a>(MutableArrayIntoIterInner<'a>);
+
+enum MutableArrayIntoIterInner<'a> {
+    Borrowed(RawArrayIter<'a>),
+    Owned(std::vec::IntoIter<MutableValue<'a>>),
+}
+
+impl<'a> Iterator for MutableArrayIntoIter<'a> {
+    type Item = Result<MutableValue<'a>, bson::raw::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            MutableArrayIntoIterInner::Borrowed(it) => it.next().map(|v| v.map(MutableValue::from)),
+            MutableArrayIntoIterInner::Owned(it) => it.next().map(Ok),
+        }
+    }
+}
+
+/// A borrowing iterator over the elements of a [`MutableArray`] in order.
+///
+/// Elements of an encoded array are decoded as they are visited without parsing the whole array.
+pub struct MutableArrayIter<'i, 'a>(MutableArrayIterInner<'i, 'a>);
+
+enum MutableArrayIterInner<'i, 'a> {
+    Borrowed(RawArrayIter<'a>),
+    Owned(std::slice::Iter<'i, MutableValue<'a>>),
+}
+
+impl<'i, 'a> Iterator for MutableArrayIter<'i, 'a> {
+    type Item = Result<Cow<'i, MutableValue<'a>>, bson::raw::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            MutableArrayIterInner::Borrowed(it) => {
+                it.next().map(|v| v.map(|v| Cow::Owned(v.into())))
+            }
+            MutableArrayIterInner::Owned(it) => it.next().map(|v| Ok(Cow::Borrowed(v))),
+        }
+    }
+}
+
+/// Iterate over the elements of the array by reference. Yields a raw BSON parsing error if the
+/// array is malformed.
+impl<'i, 'a> IntoIterator for &'i MutableArray<'a> {
+    type Item = Result<Cow<'i, MutableValue<'a>>, bson::raw::Error>;
+    type IntoIter = MutableArrayIter<'i, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Arrays are equal if they contain equal values in the same order.
+///
+/// Two borrowed arrays are compared by their encoded bytes. When comparing a borrowed array to an
+/// owned array the borrowed array is parsed; if it is malformed they are not equal.
+impl PartialEq for MutableArray<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Borrowed(a), Self::Borrowed(b)) => a.as_bytes() == b.as_bytes(),
+            (Self::Owned(a), Self::Owned(b)) => a == b,
+            (Self::Borrowed(r), Self::Owned(p)) | (Self::Owned(p), Self::Borrowed(r)) => {
+                Self::encoded_to_parsed(r).is_ok_and(|r| r == *p)
+            }
+        }
+    }
+}
+
+/// The default array is an empty parsed array.
+impl Default for MutableArray<'_> {
+    fn default() -> Self {
+        Self::Owned(vec![])
+    }
+}
+
+impl<'a> From<&'a RawArray> for MutableArray<'a> {
+    fn from(value: &'a RawArray) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl<'a> From<Vec<MutableValue<'a>>> for MutableArray<'a> {
+    fn from(value: Vec<MutableValue<'a>>) -> Self {
+        Self::Owned(value)
+    }
+}
+
+impl From<Vec<Bson>> for MutableArray<'_> {
+    fn from(value: Vec<Bson>) -> Self {
+        Self::Owned(value.into_iter().map(MutableValue::from).collect())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum MutableBinary<'a> {
+    Borrowed(RawBinaryRef<'a>),
+    Owned(Binary),
+}
+
+impl MutableBinary<'_> {
+    /// Create an owned binary value from bytes with the given subtype.
+    pub fn new(bytes: impl Into<Vec<u8>>, subtype: BinarySubtype) -> Self {
+        Self::Owned(Binary {
+            subtype,
+            bytes: bytes.into(),
+        })
+    }
+
+    /// Create an owned binary value from bytes with the generic subtype.
+    pub fn new_generic(bytes: impl Into<Vec<u8>>) -> Self {
+        Self::new(bytes, BinarySubtype::Generic)
+    }
+
+    fn raw_len(&self) -> usize {
+        let (bytes, _) = self.parts();
+        // length of the byte string + 4 bytes for length + 1 byte for subtype.
+        4 + bytes.len() + 1
+    }
+
+    fn put(&self, buf: &mut impl BufMut) {
+        let (bytes, subtype) = self.parts();
+        buf.put_i32_le(
+            bytes
+                .len()
+                .try_into()
+                .expect("message len verified before put"),
+        );
+        buf.put_u8(subtype.into());
+        buf.put_slice(bytes);
+    }
+
+    /// Returns the binary data.
+    pub fn bytes(&self) -> &[u8] {
+        self.parts().0
+    }
+
+    /// Returns the binary subtype.
+    pub fn subtype(&self) -> BinarySubtype {
+        self.parts().1
+    }
+
+    /// Replace the bytes, copying any borrowed data.
+    pub fn set_bytes(&mut self, bytes: impl Into<Vec<u8>>) {
+        self.make_owned().bytes = bytes.into();
+    }
+
+    /// Get a mutable reference to the bytes, copying any borrowed data.
+    pub fn bytes_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.make_owned().bytes
+    }
+
+    /// Replace the subtype, copying any borrowed data.
+    pub fn set_subtype(&mut self, subtype: BinarySubtype) {
+        self.make_owned().subtype = subtype;
+    }
+
+    fn make_owned(&mut self) -> &mut Binary {
+        *self = match self {
+            Self::Owned(v) => return v,
+            Self::Borrowed(v) => Self::Owned(v.to_binary()),
+        };
+        self.make_owned()
+    }
+
+    /// Copy the contents into a [`bson::Binary`].
+    pub fn into_binary(self) -> Binary {
+        match self {
+            Self::Borrowed(v) => Binary {
+                subtype: v.subtype,
+                bytes: v.bytes.to_vec(),
+            },
+            Self::Owned(v) => v,
+        }
+    }
+
+    fn parts(&self) -> (&[u8], BinarySubtype) {
+        match self {
+            Self::Borrowed(v) => (v.bytes, v.subtype),
+            Self::Owned(v) => (v.bytes.as_ref(), v.subtype),
+        }
+    }
+}
+
+impl PartialEq for MutableBinary<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl<'a> From<RawBinaryRef<'a>> for MutableBinary<'a> {
+    fn from(value: RawBinaryRef<'a>) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl From<Binary> for MutableBinary<'_> {
+    fn from(value: Binary) -> Self {
+        Self::Owned(value)
+    }
+}
+
+/// Error returned by [`MutableRegex::new()`] for a pattern or options that cannot be encoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidRegexError {
+    /// The pattern or options contain a null byte.
+    NullByte,
+    /// The options contain a character other than `i`, `m`, `s` or `x`.
+    InvalidOption(char),
+}
+
+impl std::fmt::Display for InvalidRegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NullByte => write!(f, "regex pattern or options contain a null byte"),
+            Self::InvalidOption(c) => write!(f, "invalid regex option {:?}", c),
+        }
+    }
+}
+
+impl std::error::Error for InvalidRegexError {}
+
+#[derive(Clone, Debug)]
+pub enum MutableRegex<'a> {
+    Borrowed(RawRegexRef<'a>),
+    Owned(Regex),
+}
+
+impl MutableRegex<'_> {
+    /// Create an owned regular expression, validating that it can be encoded.
+    ///
+    /// Options are stored in alphabetical order as required by the BSON spec.
+    pub fn new(pattern: &str, options: &str) -> Result<Self, InvalidRegexError> {
+        if pattern.contains('\0') || options.contains('\0') {
+            return Err(InvalidRegexError::NullByte);
+        }
+        if let Some(c) = options.chars().find(|c| !"imsx".contains(*c)) {
+            return Err(InvalidRegexError::InvalidOption(c));
+        }
+        let mut options = options.chars().collect::<Vec<_>>();
+        options.sort_unstable();
+        Ok(Self::Owned(Regex {
+            pattern: pattern.to_string(),
+            options: options.into_iter().collect(),
+        }))
+    }
+
+    fn raw_len(&self) -> usize {
+        let (pattern, options) = self.parts();
+        raw_cstr_len(pattern) + raw_cstr_len(options)
+    }
+
+    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
+        let (pattern, options) = self.parts();
+        put_raw_cstr(pattern, buf)?;
+        put_raw_cstr(options, buf)
+    }
+
+    /// Returns the regular expression pattern.
+    pub fn pattern(&self) -> &str {
+        self.parts().0
+    }
+
+    /// Returns the regular expression options.
+    pub fn options(&self) -> &str {
+        self.parts().1
+    }
+
+    /// Replace the pattern, copying any borrowed data.
+    pub fn set_pattern(&mut self, pattern: impl Into<String>) {
+        self.make_owned().pattern = pattern.into();
+    }
+
+    /// Replace the options, copying any borrowed data.
+    pub fn set_options(&mut self, options: impl Into<String>) {
+        self.make_owned().options = options.into();
+    }
+
+    fn make_owned(&mut self) -> &mut Regex {
+        *self = match self {
+            Self::Owned(v) => return v,
+            Self::Borrowed(v) => Self::Owned(Regex {
+                pattern: v.pattern.to_owned(),
+                options: v.options.to_owned(),
+            }),
+        };
+        self.make_owned()
+    }
+
+    /// Copy the contents into a [`bson::Regex`].
+    pub fn into_regex(self) -> Regex {
+        match self {
+            Self::Borrowed(v) => Regex {
+                pattern: v.pattern.to_owned(),
+                options: v.options.to_owned(),
+            },
+            Self::Owned(v) => v,
+        }
+    }
+
+    fn parts(&self) -> (&str, &str) {
+        match self {
+            Self::Borrowed(v) => (v.pattern, v.options),
+            Self::Owned(v) => (v.pattern.as_ref(), v.options.as_ref()),
+        }
+    }
+}
+
+impl PartialEq for MutableRegex<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl<'a> From<RawRegexRef<'a>> for MutableRegex<'a> {
+    fn from(value: RawRegexRef<'a>) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl From<Regex> for MutableRegex<'_> {
+    fn from(value: Regex) -> Self {
+        Self::Owned(value)
+    }
+}
+
+/// A deprecated BSON DbPointer, either borrowed from an encoded document or owned.
+///
+/// The `bson` crate does not expose the namespace or id of a DbPointer, so computing the encoded
+/// length or comparing values requires encoding the value.
+#[derive(Clone, Debug)]
+pub enum MutableDbPointer<'a> {
+    Borrowed(RawDbPointerRef<'a>),
+    Owned(DbPointer),
+}
+
+impl MutableDbPointer<'_> {
+    // The bson crate does not expose the namespace or id of a DbPointer, so the value is encoded
+    // as the only field of a document with an empty key and the value bytes are sliced out of it.
+    // 4 bytes for doc length, 1 byte for type, 1 byte for the empty key null terminator.
+    const ENCODED_VALUE_START: usize = 6;
+
+    /// Returns the encoded value: the namespace as a length prefixed string followed by the id.
+    fn encode(&self) -> Vec<u8> {
+        let mut doc = RawDocumentBuf::new();
+        match self {
+            Self::Borrowed(v) => doc.append_ref("", RawBsonRef::DbPointer(*v)),
+            Self::Owned(v) => doc.append("", RawBson::DbPointer(v.clone())),
+        }
+        let mut bytes = doc.into_bytes();
+        // Strip the doc null terminator, then the doc header.
+        bytes.pop();
+        bytes.drain(..Self::ENCODED_VALUE_START);
+        bytes
+    }
+
+    fn raw_len(&self) -> usize {
+        self.encode().len()
+    }
+
+    fn put(&self, buf: &mut impl BufMut) {
+        buf.put_slice(&self.encode());
+    }
+
+    /// Copy the contents into a [`bson::Bson::DbPointer`].
+    fn into_bson(self) -> Result<Bson, bson::raw::Error> {
+        match self {
+            Self::Borrowed(v) => Bson::try_from(RawBsonRef::DbPointer(v)),
+            Self::Owned(v) => Ok(Bson::DbPointer(v)),
+        }
+    }
+}
+
+impl PartialEq for MutableDbPointer<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Borrowed(a), Self::Borrowed(b)) => a == b,
+            (Self::Owned(a), Self::Owned(b)) => a == b,
+            _ => self.encode() == other.encode(),
+        }
+    }
+}
+
+impl<'a> From<RawDbPointerRef<'a>> for MutableDbPointer<'a> {
+    fn from(value: RawDbPointerRef<'a>) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl From<DbPointer> for MutableDbPointer<'_> {
+    fn from(value: DbPointer) -> Self {
+        Self::Owned(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum MutableJavaScriptCodeWithScope<'a> {
+    Borrowed(RawJavaScriptCodeWithScopeRef<'a>),
+    Owned(JavaScriptCodeWithScope),
+    /// Code with a scope document that can be mutated like any other [`ParsedDocument`].
+    Parsed {
+        code: Cow<'a, str>,
+        scope: ParsedDocument<'a>,
+    },
+}
+
+impl<'a> MutableJavaScriptCodeWithScope<'a> {
+    fn raw_len(&self) -> usize {
+        match self {
+            Self::Borrowed(v) => 4 + raw_str_len(v.code) + v.scope.as_bytes().len(),
+            Self::Owned(v) => {
+                4 + raw_str_len(&v.code) + ParsedDocument::from(v.scope.clone()).raw_len()
+            }
+            Self::Parsed { code, scope } => 4 + raw_str_len(code) + scope.raw_len(),
+        }
+    }
+
+    fn put(&self, buf: &mut impl BufMut) -> Result<(), bson::ser::Error> {
+        match self {
+            Self::Borrowed(v) => {
+                buf.put_i32_le(
+                    (raw_str_len(v.code) + v.scope.as_bytes().len() + 4)
+                        .try_into()
+                        .expect("document length verified before put()"),
+                );
+                put_raw_str(v.code, buf);
+                buf.put_slice(v.scope.as_bytes());
+            }
+            Self::Owned(v) => {
+                let encoded_scope = bson::to_vec(&v.scope)?;
+                buf.put_i32_le(
+                    (raw_str_len(&v.code) + encoded_scope.len() + 4)
+                        .try_into()
+                        .expect("document length verified before put()"),
+                );
+                put_raw_str(&v.code, buf);
+                buf.put_slice(&encoded_scope);
+            }
+            Self::Parsed { code, scope } => {
+                buf.put_i32_le(
+                    self.raw_len()
+                        .try_into()
+                        .expect("document length verified before put()"),
+                );
+                put_raw_str(code, buf);
+                scope.put(buf)?;
+            }
+        };
+        Ok(())
+    }
+
+    /// Convert the representation to [`MutableJavaScriptCodeWithScope::Parsed`] if necessary and
+    /// return a mutable reference to the scope document.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn to_parsed(&mut self) -> Result<&mut ParsedDocument<'a>, bson::raw::Error> {
+        *self = match self {
+            Self::Parsed { scope, .. } => return Ok(scope),
+            Self::Borrowed(v) => Self::Parsed {
+                code: v.code.into(),
+                scope: ParsedDocument::try_from(v.scope)?,
+            },
+            Self::Owned(v) => Self::Parsed {
+                code: std::mem::take(&mut v.code).into(),
+                scope: std::mem::take(&mut v.scope).into(),
+            },
+        };
+        // Now parsed, so this returns the scope without converting again.
+        self.to_parsed()
+    }
+
+    /// Copy any borrowed data so that the value has a `'static` lifetime.
+    ///
+    /// An encoded scope is parsed, which may fail with a raw BSON parsing error.
+    pub fn into_owned(self) -> Result<MutableJavaScriptCodeWithScope<'static>, bson::raw::Error> {
+        Ok(match self {
+            Self::Borrowed(v) => MutableJavaScriptCodeWithScope::Parsed {
+                code: v.code.to_owned().into(),
+                scope: ParsedDocument::try_from(v.scope)?.into_owned()?,
+            },
+            Self::Owned(v) => MutableJavaScriptCodeWithScope::Owned(v),
+            Self::Parsed { code, scope } => MutableJavaScriptCodeWithScope::Parsed {
+                code: code.into_owned().into(),
+                scope: scope.into_owned()?,
+            },
+        })
+    }
+
+    /// Copy the contents into a [`bson::JavaScriptCodeWithScope`].
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn try_into_code_with_scope(self) -> Result<JavaScriptCodeWithScope, bson::raw::Error> {
+        match self {
+            Self::Borrowed(v) => Ok(JavaScriptCodeWithScope {
+                code: v.code.to_owned(),
+                scope: Document::try_from(v.scope)?,
+            }),
+            Self::Owned(v) => Ok(v),
+            Self::Parsed { code, scope } => Ok(JavaScriptCodeWithScope {
+                code: code.into_owned(),
+                scope: scope.try_into_document()?,
+            }),
+        }
+    }
+
+    fn code(&self) -> &str {
+        match self {
+            Self::Borrowed(v) => v.code,
+            Self::Owned(v) => &v.code,
+            Self::Parsed { code, .. } => code,
+        }
+    }
+
+    fn encoded_scope(&self) -> Result<Cow<'_, [u8]>, bson::ser::Error> {
+        match self {
+            Self::Borrowed(v) => Ok(v.scope.as_bytes().into()),
+            Self::Owned(v) => bson::to_vec(&v.scope).map(Cow::from),
+            Self::Parsed { scope, .. } => scope.to_vec().map(Cow::from),
+        }
+    }
+}
+
+/// Code with scope values are equal if the code is equal and the encoded scope documents are equal.
+impl PartialEq for MutableJavaScriptCodeWithScope<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.code() == other.code()
+            && matches!(
+                (self.encoded_scope(), other.encoded_scope()),
+                (Ok(a), Ok(b)) if a == b
+            )
+    }
+}
+
+impl<'a> From<RawJavaScriptCodeWithScopeRef<'a>> for MutableJavaScriptCodeWithScope<'a> {
+    fn from(value: RawJavaScriptCodeWithScopeRef<'a>) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl From<JavaScriptCodeWithScope> for MutableJavaScriptCodeWithScope<'_> {
+    fn from(value: JavaScriptCodeWithScope) -> Self {
+        Self::Owned(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use bson::{
+        Binary, Bson, Decimal128, Document, JavaScriptCodeWithScope, RawBson, RawBsonRef, Regex,
+        doc,
+        oid::ObjectId,
+        rawdoc,
+        spec::{BinarySubtype, ElementType},
+        to_raw_document_buf,
+    };
+
+    use crate::{
+        InvalidRegexError, MutableArray, MutableBinary, MutableDocument,
+        MutableJavaScriptCodeWithScope, MutableRegex, MutableValue, ParsedDocument,
+        SerializeOptions, WrongType, compare_values,
+    };
+
+    #[test]
+    fn it_works() {}
+
+    #[test]
+    fn array_as_raw_bytes() {
+        let raw = rawdoc! { "a": [1, 2] };
+        let mut array = MutableArray::from(raw.get_array("a").unwrap());
+        assert_eq!(
+            array.as_raw_bytes(),
+            Some(raw.get_array("a").unwrap().as_bytes())
+        );
+        array.push(3).unwrap();
+        assert_eq!(array.as_raw_bytes(), None);
+    }
+
+    #[test]
+    fn document_as_raw_bytes() {
+        let raw = rawdoc! { "a": 1 };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.as_raw_bytes(), Some(raw.as_bytes()));
+        let raw_ref = doc.as_raw().unwrap();
+        assert_eq!(AsRef::<[u8]>::as_ref(&raw_ref), raw.as_bytes());
+        assert_eq!(raw_ref.as_raw_document().get_i32("a"), Ok(1));
+        doc.to_parsed().unwrap();
+        assert_eq!(doc.as_raw_bytes(), None);
+        assert!(doc.as_raw().is_none());
+    }
+
+    #[test]
+    fn document_byte_len() {
+        let raw = rawdoc! { "a": 1 };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.byte_len(), Some(12));
+        doc.to_parsed().unwrap();
+        assert_eq!(doc.byte_len(), None);
+    }
+
+    #[test]
+    fn document_field_types() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "d" } };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        let expected = vec![
+            ("a", ElementType::Int32),
+            ("b", ElementType::EmbeddedDocument),
+        ];
+        assert_eq!(
+            doc.field_types().collect::<Result<Vec<_>, _>>().unwrap(),
+            expected
+        );
+        doc.to_parsed().unwrap();
+        assert_eq!(
+            doc.field_types().collect::<Result<Vec<_>, _>>().unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn document_count_by_type() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "d", "e": 2 }, "f": [3, "g"], "h": 4 };
+        let doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(
+            doc.count_by_type().unwrap(),
+            vec![
+                (ElementType::Int32, 2),
+                (ElementType::EmbeddedDocument, 1),
+                (ElementType::Array, 1)
+            ]
+        );
+        assert_eq!(
+            doc.count_by_type_recursive().unwrap(),
+            vec![
+                (ElementType::Int32, 4),
+                (ElementType::EmbeddedDocument, 1),
+                (ElementType::String, 2),
+                (ElementType::Array, 1)
+            ]
+        );
+        assert!(
+            MutableDocument::default()
+                .count_by_type()
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn size_hint() {
+        let raw = rawdoc! { "a": 1, "b": [1, "two", { "c": 3 }] };
+        let raw_len = raw.as_bytes().len();
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.size_hint(), (raw_len, Some(raw_len)));
+        let array = doc.to_parsed().unwrap().get_array_mut("b").unwrap();
+        let array_len = raw.get_array("b").unwrap().as_bytes().len();
+        assert_eq!(array.size_hint(), (array_len, Some(array_len)));
+        array.push(4).unwrap();
+        assert_eq!(array.size_hint(), (5 + 4 * 3, None));
+        assert_eq!(doc.size_hint(), (5 + 2 * 2, None));
+        assert_eq!(doc.to_vec().unwrap().len(), raw_len + 7);
+        assert_eq!(doc.size_hint(), (raw_len + 7, Some(raw_len + 7)));
+    }
+
+    fn doc_nested_types() -> Document {
+        doc! {
+            "a": 1.5,
+            "b": "str",
+            "c": { "d": [1, "two", { "e": 3i64 }] },
+            "f": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] },
+            "g": Regex { pattern: "foo.*".into(), options: "i".into() },
+            "h": Bson::JavaScriptCodeWithScope(JavaScriptCodeWithScope {
+                code: "code".into(),
+                scope: doc! { "x": 1 },
+            }),
+            "i": Bson::Symbol("sym".into()),
+        }
+    }
+
+    #[test]
+    fn to_bson_borrowed() {
+        let raw = to_raw_document_buf(&doc_nested_types()).unwrap();
+        let value = MutableValue::Document(raw.as_ref().into());
+        assert_eq!(
+            Bson::try_from(value).unwrap(),
+            Bson::Document(doc_nested_types())
+        );
+    }
+
+    #[test]
+    fn to_bson_parsed() {
+        let raw = to_raw_document_buf(&doc_nested_types()).unwrap();
+        let mut value = MutableValue::Document(raw.as_ref().into());
+        let doc = value.as_doc_mut().unwrap().to_parsed().unwrap();
+        for (_, v) in doc.iter_mut() {
+            if let Some(d) = v.as_doc_mut() {
+                d.to_parsed().unwrap();
+            }
+        }
+        assert_eq!(
+            Bson::try_from(value).unwrap(),
+            Bson::Document(doc_nested_types())
+        );
+        assert_eq!(
+            Bson::try_from(MutableValue::from(Bson::Document(doc_nested_types()))).unwrap(),
+            Bson::Document(doc_nested_types())
+        );
+    }
+
+    #[test]
+    fn to_raw_bson_ref() {
+        let raw = to_raw_document_buf(&doc_nested_types()).unwrap();
+        for (k, v) in raw.iter().map(Result::unwrap) {
+            assert_eq!(
+                RawBsonRef::try_from(MutableValue::from(v)).unwrap(),
+                v,
+                "{}",
+                k
+            );
+        }
+
+        assert_eq!(
+            RawBsonRef::try_from(MutableValue::from(7)).unwrap(),
+            RawBsonRef::Int32(7)
+        );
+        assert!(RawBsonRef::try_from(MutableValue::from("owned")).is_err());
+        assert!(
+            RawBsonRef::try_from(MutableValue::from(Bson::Document(doc_nested_types()))).is_err()
+        );
+    }
+
+    #[test]
+    fn value_eq() {
+        assert_eq!(MutableValue::from(1), MutableValue::from(1));
+        assert_ne!(MutableValue::from(1), MutableValue::from(1i64));
+        assert_ne!(MutableValue::from(1), MutableValue::from(1.0));
+        assert_eq!(MutableValue::from("foo"), MutableValue::from("foo"));
+        assert_eq!(MutableValue::Null, MutableValue::Null);
+        assert_ne!(MutableValue::Null, MutableValue::Undefined);
+    }
+
+    #[test]
+    fn value_eq_double_bits() {
+        assert_eq!(MutableValue::from(f64::NAN), MutableValue::from(f64::NAN));
+        assert_ne!(MutableValue::from(0.0), MutableValue::from(-0.0));
+    }
+
+    #[test]
+    fn document_eq() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "d" } };
+        let raw_copy = raw.clone();
+        let borrowed = MutableDocument::from(raw.as_ref());
+        let owned = MutableDocument::from(raw.as_ref())
+            .try_into_parsed()
+            .unwrap();
+        assert_eq!(borrowed, MutableDocument::from(raw_copy.as_ref()));
+        assert_eq!(borrowed, owned);
+        assert_eq!(owned, borrowed);
+        assert_eq!(owned, owned.clone());
+
+        let mut changed = owned.clone();
+        changed
+            .get_mut("b")
+            .unwrap()
+            .and_then(MutableValue::as_doc_mut)
+            .unwrap()
+            .insert("c", "e")
+            .unwrap();
+        assert_ne!(borrowed, changed);
+        assert_ne!(changed, borrowed);
+
+        let reordered = rawdoc! { "b": { "c": "d" }, "a": 1 };
+        assert_ne!(borrowed, MutableDocument::from(reordered.as_ref()));
+        assert_ne!(
+            owned,
+            MutableDocument::from(reordered.as_ref())
+                .try_into_parsed()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn array_eq() {
+        let raw = rawdoc! { "v": [1, "two", 3.0] };
+        let borrowed = MutableArray::from(raw.get_array("v").unwrap());
+        let owned = MutableArray::from(vec![
+            MutableValue::from(1),
+            MutableValue::from("two"),
+            MutableValue::from(3.0),
+        ]);
+        assert_eq!(borrowed, borrowed.clone());
+        assert_eq!(borrowed, owned);
+        assert_eq!(owned, borrowed);
+        assert_ne!(owned, MutableArray::from(vec![MutableValue::from(1)]));
+    }
+
+    #[test]
+    fn array_accessors() {
+        let raw = rawdoc! { "v": [1, "two", 3.0] };
+        let mut array = MutableArray::from(raw.get_array("v").unwrap());
+        assert_eq!(array.len().unwrap(), 3);
+        assert!(!array.is_empty());
+        assert_eq!(array.get(1).unwrap().unwrap().as_str(), Some("two"));
+        assert!(array.get(3).unwrap().is_none());
+        assert!(matches!(array, MutableArray::Borrowed(_)));
+
+        array.push(4).unwrap();
+        assert!(matches!(array, MutableArray::Owned(_)));
+        assert_eq!(array.len().unwrap(), 4);
+        assert_eq!(array.get(1).unwrap().unwrap().as_str(), Some("two"));
+        *array.get_mut(0).unwrap().unwrap() = false.into();
+        assert!(array.get_mut(4).unwrap().is_none());
+        assert_eq!(array.pop().unwrap(), Some(MutableValue::from(4)));
+        assert_eq!(array.remove(1).unwrap(), Some(MutableValue::from("two")));
+        assert!(array.remove(2).unwrap().is_none());
+        assert_eq!(
+            array,
+            MutableArray::from(vec![MutableValue::from(false), MutableValue::from(3.0)])
+        );
+    }
+
+    #[test]
+    fn array_group_by_type() {
+        let raw = rawdoc! { "v": [1, "two", 3, { "a": 4 }, "five"] };
+        let array = MutableArray::from(raw.get_array("v").unwrap());
+        let groups = array.group_by_type().unwrap();
+        assert_eq!(
+            groups
+                .iter()
+                .map(|(t, g)| (*t, g.len()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ElementType::Int32, 2),
+                (ElementType::String, 2),
+                (ElementType::EmbeddedDocument, 1)
+            ]
+        );
+        assert_eq!(
+            groups[1].1,
+            vec![MutableValue::from("two"), MutableValue::from("five")]
+        );
+        assert!(MutableArray::default().group_by_type().unwrap().is_empty());
+    }
+
+    #[test]
+    fn array_map_into() {
+        let raw = rawdoc! { "v": [1, "two", 3] };
+        let mut array = MutableArray::from(raw.get_array("v").unwrap());
+        array
+            .map_into(|v| match v {
+                MutableValue::Int32(i) => MutableValue::Int64(i.into()),
+                v => v,
+            })
+            .unwrap();
+        assert_eq!(
+            array,
+            MutableArray::from(vec![
+                MutableValue::Int64(1),
+                MutableValue::from("two"),
+                MutableValue::Int64(3)
+            ])
+        );
+    }
+
+    #[test]
+    fn array_retain() {
+        let raw = rawdoc! { "v": [1, "two", 3, null] };
+        let mut array = MutableArray::from(raw.get_array("v").unwrap());
+        array
+            .retain(|v| {
+                if let Some(i) = v.as_i32() {
+                    *v = (i * 10).into();
+                }
+                *v != MutableValue::Null
+            })
+            .unwrap();
+        assert_eq!(
+            array,
+            MutableArray::from(vec![
+                MutableValue::from(10),
+                MutableValue::from("two"),
+                MutableValue::from(30)
+            ])
+        );
+    }
+
+    #[test]
+    fn to_vec() {
+        let raw = rawdoc! { "a": 1, "v": [1, "two"] };
+        let doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.to_vec().unwrap(), raw.as_bytes());
+        assert_eq!(
+            doc.try_into_parsed().unwrap().to_vec().unwrap(),
+            raw.as_bytes()
+        );
+
+        let array = MutableArray::from(raw.get_array("v").unwrap());
+        assert_eq!(
+            array.to_vec().unwrap(),
+            raw.get_array("v").unwrap().as_bytes()
+        );
+        assert_eq!(
+            array.try_into_parsed().unwrap().to_vec().unwrap(),
+            raw.get_array("v").unwrap().as_bytes()
+        );
+    }
+
+    #[test]
+    fn to_vec_too_large() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", "x".repeat(32 << 20));
+        assert!(doc.to_vec().is_err());
+        assert!(MutableDocument::from(doc).to_vec().is_err());
+    }
+
+    #[test]
+    fn to_vec_with_options() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", "x".repeat(32 << 20));
+        let unlimited = SerializeOptions {
+            max_document_size: None,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(
+            doc.to_vec_with_options(&unlimited).unwrap().len(),
+            doc.raw_len()
+        );
+
+        let doc = MutableDocument::from(parsed_doc! { "a": "xyz" });
+        let limit = |n| SerializeOptions {
+            max_document_size: Some(n),
+            ..SerializeOptions::default()
+        };
+        assert_eq!(doc.to_vec_with_options(&limit(16)).unwrap().len(), 16);
+        assert!(doc.to_vec_with_options(&limit(15)).is_err());
+    }
+
+    #[test]
+    fn code_with_scope_to_parsed() {
+        let code_with_scope = JavaScriptCodeWithScope {
+            code: "code".into(),
+            scope: doc! { "x": 1 },
+        };
+        let raw = rawdoc! { "c": RawBson::try_from(Bson::JavaScriptCodeWithScope(code_with_scope.clone())).unwrap() };
+        let borrowed = MutableJavaScriptCodeWithScope::from(
+            raw.get("c")
+                .unwrap()
+                .unwrap()
+                .as_javascript_with_scope()
+                .unwrap(),
+        );
+        let owned = MutableJavaScriptCodeWithScope::from(code_with_scope);
+        for mut v in [borrowed, owned] {
+            let expected = v.clone();
+            v.to_parsed().unwrap();
+            assert!(matches!(v, MutableJavaScriptCodeWithScope::Parsed { .. }));
+            assert_eq!(v, expected);
+
+            v.to_parsed().unwrap().insert("y", "z");
+            let mut buf = vec![];
+            v.put(&mut buf).unwrap();
+            assert_eq!(buf.len(), v.raw_len());
+            assert_eq!(
+                v.try_into_code_with_scope().unwrap(),
+                JavaScriptCodeWithScope {
+                    code: "code".into(),
+                    scope: doc! { "x": 1, "y": "z" },
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn document_accessors() {
+        let raw = rawdoc! { "a": 1, "b": "two" };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.len().unwrap(), 2);
+        assert!(!doc.is_empty());
+        assert!(doc.contains_key("b").unwrap());
+        assert!(!doc.contains_key("c").unwrap());
+        assert_eq!(doc.get("b").unwrap().unwrap().as_str(), Some("two"));
+        assert!(doc.get("c").unwrap().is_none());
+        assert!(matches!(doc, MutableDocument::Borrowed(_)));
+
+        assert_eq!(
+            doc.insert("a", 3).unwrap().and_then(|v| v.as_i32()),
+            Some(1)
+        );
+        assert!(matches!(doc, MutableDocument::Owned(_)));
+        *doc.get_mut("b").unwrap().unwrap() = MutableValue::Boolean(true);
+        assert!(doc.insert("c", "x").unwrap().is_none());
+        assert_eq!(doc.remove("a").unwrap().unwrap().as_i32(), Some(3));
+        assert!(doc.remove("a").unwrap().is_none());
+        assert_eq!(doc.len().unwrap(), 2);
+        assert!(doc.contains_key("c").unwrap());
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "b": true, "c": "x" }.as_bytes()
+        );
+
+        let empty = rawdoc! {};
+        assert!(MutableDocument::from(empty.as_ref()).is_empty());
+        assert!(MutableDocument::from(ParsedDocument::new()).is_empty());
+    }
+
+    #[test]
+    fn deep_clone_static() {
+        let raw = to_raw_document_buf(&doc_nested_types()).unwrap();
+        let doc = MutableDocument::from(raw.as_ref());
+        let clone = doc.deep_clone_static().unwrap();
+        drop(doc);
+        drop(raw);
+        assert_eq!(clone.try_into_document().unwrap(), doc_nested_types());
+    }
+
+    #[test]
+    fn array_first_last() {
+        let raw = rawdoc! { "a": [1, "two", 3i64] };
+        let mut array = MutableArray::from(raw.get_array("a").unwrap());
+        assert_eq!(array.first().unwrap().unwrap().as_i32(), Some(1));
+        assert_eq!(array.last().unwrap().unwrap().as_i64(), Some(3));
+        assert!(matches!(array, MutableArray::Borrowed(_)));
+
+        *array.first_mut().unwrap().unwrap() = MutableValue::Boolean(true);
+        *array.last_mut().unwrap().unwrap() = MutableValue::Null;
+        assert_eq!(array.first().unwrap().unwrap().as_bool(), Some(true));
+        assert!(array.last().unwrap().unwrap().is_null());
+
+        let empty = rawdoc! { "a": [] };
+        let mut array = MutableArray::from(empty.get_array("a").unwrap());
+        assert!(array.first().unwrap().is_none());
+        assert!(array.last().unwrap().is_none());
+        assert!(array.first_mut().unwrap().is_none());
+        assert!(array.last_mut().unwrap().is_none());
+    }
+
+    #[test]
+    fn array_iter() {
+        let raw = rawdoc! { "a": [1, "two", 3i64] };
+        let mut array = MutableArray::from(raw.get_array("a").unwrap());
+        for _ in 0..2 {
+            assert_eq!(
+                array
+                    .iter()
+                    .map(|v| v.unwrap().type_name())
+                    .collect::<Vec<_>>(),
+                vec!["Int32", "String", "Int64"]
+            );
+            array.to_parsed().unwrap();
+        }
+        assert_eq!((&array).into_iter().count(), 3);
+    }
+
+    #[test]
+    fn array_contains() {
+        let raw = rawdoc! { "a": [1, "two", 1, { "x": 3 }] };
+        let mut array = MutableArray::from(raw.get_array("a").unwrap());
+        for _ in 0..2 {
+            assert!(array.contains(&MutableValue::from("two")).unwrap());
+            assert!(!array.contains(&MutableValue::from(1i64)).unwrap());
+            assert_eq!(array.position(&MutableValue::from(1)).unwrap(), Some(0));
+            assert_eq!(
+                array
+                    .position(&MutableValue::from(parsed_doc! { "x": 3 }))
+                    .unwrap(),
+                Some(3)
+            );
+            array.to_parsed().unwrap();
+        }
+    }
+
+    #[test]
+    fn array_dedup() {
+        let raw = rawdoc! { "v": ["a", "a", "b", "a", null, null] };
+        let mut array = MutableArray::from(raw.get_array("v").unwrap());
+        array.dedup().unwrap();
+        array.retain(|v| !v.is_null()).unwrap();
+        assert_eq!(
+            array.to_vec().unwrap(),
+            rawdoc! { "0": "a", "1": "b", "2": "a" }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn array_extend_append() {
+        let raw = rawdoc! { "a": [1, 2], "b": ["x"] };
+        let mut a = MutableArray::from(raw.get_array("a").unwrap());
+        let mut b = MutableArray::from(raw.get_array("b").unwrap());
+        a.extend([MutableValue::from(3), MutableValue::Null])
+            .unwrap();
+        a.append(&mut b).unwrap();
+        assert!(b.is_empty());
+        assert_eq!(
+            a.to_vec().unwrap(),
+            rawdoc! { "0": 1, "1": 2, "2": 3, "3": null, "4": "x" }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn array_drain_split_off() {
+        let raw = rawdoc! { "a": [0, 1, 2, 3, 4, 5] };
+        let mut array = MutableArray::from(raw.get_array("a").unwrap());
+        assert_eq!(
+            array
+                .drain(1..3)
+                .unwrap()
+                .filter_map(|v| v.as_i32())
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let tail = array.split_off(2).unwrap();
+        assert_eq!(
+            array.to_vec().unwrap(),
+            rawdoc! { "0": 0, "1": 3 }.as_bytes()
+        );
+        assert_eq!(
+            tail.to_vec().unwrap(),
+            rawdoc! { "0": 4, "1": 5 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn array_empty() {
+        let raw = rawdoc! { "v": [] };
+        let mut array = MutableArray::from(raw.get_array("v").unwrap());
+        assert!(array.is_empty());
+        assert_eq!(array.len().unwrap(), 0);
+        assert!(array.pop().unwrap().is_none());
+        assert!(array.is_empty());
+    }
+
+    #[test]
+    fn truthiness() {
+        let zero_decimal: Decimal128 = "0E+10".parse().unwrap();
+        for falsy in [
+            MutableValue::Boolean(false),
+            MutableValue::Int32(0),
+            MutableValue::Int64(0),
+            MutableValue::Double(-0.0),
+            MutableValue::Decimal128(zero_decimal),
+            MutableValue::Null,
+            MutableValue::Undefined,
+        ] {
+            assert!(falsy.is_falsy(), "{:?}", falsy);
+        }
+
+        let one_decimal: Decimal128 = "0.1".parse().unwrap();
+        let nan_decimal: Decimal128 = "NaN".parse().unwrap();
+        for truthy in [
+            MutableValue::Boolean(true),
+            MutableValue::Int32(-1),
+            MutableValue::Int64(2),
+            MutableValue::Double(f64::NAN),
+            MutableValue::Decimal128(one_decimal),
+            MutableValue::Decimal128(nan_decimal),
+            MutableValue::from(""),
+            MutableValue::from(ParsedDocument::new()),
+            MutableValue::from(Vec::<MutableValue>::new()),
+            MutableValue::MinKey,
+        ] {
+            assert!(truthy.is_truthy(), "{:?}", truthy);
+        }
+    }
+
+    #[test]
+    fn code_with_scope_owned_invalid_key() {
+        let mut doc = ParsedDocument::new();
+        let mut scope = Document::new();
+        scope.insert("a\0", 1);
+        doc.insert(
+            "c",
+            Bson::JavaScriptCodeWithScope(JavaScriptCodeWithScope {
+                code: "x".into(),
+                scope,
+            }),
+        );
+        assert!(doc.to_vec().is_err());
+    }
+
+    #[test]
+    fn estimate_bson_size() {
+        assert_eq!(MutableValue::Int64(1).estimate_bson_size(), 8);
+        assert_eq!(MutableValue::from("abc").estimate_bson_size(), 8);
+
+        let raw = rawdoc! { "a": { "b": 1, "c": 2 }, "d": [1, 2, 3] };
+        let borrowed = MutableValue::from(raw.get("a").unwrap().unwrap());
+        assert_eq!(borrowed.estimate_bson_size(), 19);
+
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        doc.get_array_mut("d").unwrap().push(4).unwrap();
+        let value = MutableValue::from(doc);
+        assert_eq!(value.estimate_bson_size(), 5 + 2 * 16);
+        if let MutableValue::Document(MutableDocument::Owned(doc)) = &value {
+            doc.to_vec().unwrap();
+        }
+        assert_eq!(value.estimate_bson_size(), 63);
+        let array = value.as_doc().unwrap().get("d").unwrap().unwrap();
+        assert_eq!(array.estimate_bson_size(), 5 + 4 * 16);
+
+        let owned = MutableValue::from(Bson::JavaScriptCodeWithScope(JavaScriptCodeWithScope {
+            code: "abc".into(),
+            scope: doc! { "a": 1, "b": 2 },
+        }));
+        assert_eq!(owned.estimate_bson_size(), 4 + 8 + 5 + 2 * 16);
+
+        let scope = parsed_doc! { "a": 1 };
+        let parsed = MutableValue::from(MutableJavaScriptCodeWithScope::Parsed {
+            code: "abc".into(),
+            scope: scope.clone(),
+        });
+        assert_eq!(parsed.estimate_bson_size(), 4 + 8 + 5 + 16);
+        scope.to_vec().unwrap();
+        let parsed = MutableValue::from(MutableJavaScriptCodeWithScope::Parsed {
+            code: "abc".into(),
+            scope,
+        });
+        assert_eq!(parsed.estimate_bson_size(), 4 + 8 + 12);
+    }
+
+    #[test]
+    fn into_document_and_array() {
+        let raw = rawdoc! { "a": { "b": 1 }, "c": [1] };
+        let doc = MutableValue::from(raw.get("a").unwrap().unwrap());
+        assert!(matches!(
+            doc.clone().into_array(),
+            Err(MutableValue::Document(_))
+        ));
+        assert!(matches!(
+            doc.into_document(),
+            Ok(MutableDocument::Borrowed(_))
+        ));
+        let array = MutableValue::from(raw.get("c").unwrap().unwrap());
+        assert!(matches!(
+            array.clone().into_document(),
+            Err(MutableValue::Array(_))
+        ));
+        assert!(matches!(array.into_array(), Ok(MutableArray::Borrowed(_))));
+    }
+
+    #[test]
+    fn default() {
+        assert!(MutableValue::default().is_null());
+        assert!(matches!(MutableDocument::default(), MutableDocument::Owned(p) if p.is_empty()));
+        assert!(matches!(MutableArray::default(), MutableArray::Owned(v) if v.is_empty()));
+    }
+
+    #[test]
+    fn array_with_capacity() {
+        let mut array = MutableArray::with_capacity(4);
+        assert!(matches!(&array, MutableArray::Owned(v) if v.is_empty() && v.capacity() >= 4));
+        array.push(1).unwrap();
+        assert_eq!(array.len().unwrap(), 1);
+    }
+
+    #[test]
+    fn clear() {
+        let raw = rawdoc! { "a": 1, "b": [1, 2] };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        doc.clear();
+        assert!(doc.is_empty());
+        doc.insert("c", 1).unwrap();
+        doc.clear();
+        assert_eq!(doc.to_vec().unwrap(), rawdoc! {}.as_bytes());
+
+        let mut array = MutableArray::from(raw.get_array("b").unwrap());
+        array.clear();
+        assert!(array.is_empty());
+        array.push(1).unwrap();
+        array.clear();
+        assert!(array.is_empty());
+    }
+
+    #[test]
+    fn document_from_bytes() {
+        let raw = rawdoc! { "a": 1 };
+        let doc = MutableDocument::from_bytes(raw.as_bytes()).unwrap();
+        assert!(matches!(doc, MutableDocument::Borrowed(_)));
+        assert_eq!(doc.get("a").unwrap().unwrap().as_i32(), Some(1));
+        assert!(MutableDocument::from_bytes(&raw.as_bytes()[..4]).is_err());
+    }
+
+    #[test]
+    fn document_iter() {
+        let raw = rawdoc! { "a": 1, "b": "two", "c": 3i64 };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        for _ in 0..2 {
+            assert_eq!(
+                doc.iter()
+                    .map(|e| e.map(|(k, v)| (k.to_string(), v.type_name())))
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap(),
+                vec![
+                    ("a".to_string(), "Int32"),
+                    ("b".to_string(), "String"),
+                    ("c".to_string(), "Int64")
+                ]
+            );
+            doc.to_parsed().unwrap();
+        }
+        assert_eq!((&doc).into_iter().count(), 3);
+    }
+
+    #[test]
+    fn document_iter_mut() {
+        let raw = rawdoc! { "a": 1, "b": 2 };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        for (_, v) in doc.iter_mut().unwrap() {
+            *v = MutableValue::from(v.as_i32().unwrap() * 10);
+        }
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": 10, "b": 20 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn document_into_iter() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "d" } };
+        let expected = vec![
+            (Cow::from("a"), MutableValue::from(1)),
+            (
+                Cow::from("b"),
+                MutableValue::Document(raw.get_document("b").unwrap().into()),
+            ),
+        ];
+        let borrowed = MutableDocument::from(raw.as_ref());
+        assert_eq!(
+            borrowed
+                .clone()
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            borrowed
+                .try_into_parsed()
+                .unwrap()
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn array_into_iter() {
+        let raw = rawdoc! { "v": [1, "two", 3.0] };
+        let expected = vec![
+            MutableValue::from(1),
+            MutableValue::from("two"),
+            MutableValue::from(3.0),
+        ];
+        let borrowed = MutableArray::from(raw.get_array("v").unwrap());
+        assert_eq!(
+            borrowed
+                .clone()
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            MutableArray::from(expected.clone())
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn into_owned() {
+        let raw = to_raw_document_buf(&doc_nested_types()).unwrap();
+        let value = MutableValue::Document(raw.as_ref().into());
+        let owned: MutableValue<'static> = value.clone().into_owned().unwrap();
+        drop(raw);
+        assert_eq!(
+            Bson::try_from(owned).unwrap(),
+            Bson::Document(doc_nested_types())
+        );
+    }
+
+    #[test]
+    fn try_from_value() {
+        let raw = to_raw_document_buf(&doc_nested_types()).unwrap();
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        for (_, value) in doc.iter() {
+            let bson = Bson::try_from(value.clone()).unwrap();
+            match bson {
+                Bson::Double(v) => assert_eq!(f64::try_from(value), Ok(v)),
+                Bson::String(v) => assert_eq!(String::try_from(value.clone()), Ok(v)),
+                Bson::Binary(v) => assert_eq!(Binary::try_from(value), Ok(v)),
+                Bson::RegularExpression(v) => assert_eq!(Regex::try_from(value), Ok(v)),
+                _ => {}
+            }
+        }
+
+        assert_eq!(f64::try_from(MutableValue::Double(1.5)), Ok(1.5));
+        assert_eq!(bool::try_from(&MutableValue::Boolean(true)), Ok(true));
+        assert_eq!(
+            i64::try_from(MutableValue::Int32(1)),
+            Err(WrongType {
+                expected: ElementType::Int64,
+                actual: ElementType::Int32
+            })
+        );
+        assert_eq!(
+            ObjectId::try_from(&MutableValue::Null),
+            Err(WrongType {
+                expected: ElementType::ObjectId,
+                actual: ElementType::Null
+            })
+        );
+    }
+
+    #[test]
+    fn as_number() {
+        assert_eq!(MutableValue::Double(2.5).as_number_f64(), Some(2.5));
+        assert_eq!(MutableValue::Int32(-3).as_number_f64(), Some(-3.0));
+        assert_eq!(
+            MutableValue::Int64(1 << 40).as_number_f64(),
+            Some((1u64 << 40) as f64)
+        );
+        assert_eq!(MutableValue::from("1").as_number_f64(), None);
+
+        assert_eq!(MutableValue::Int32(-3).as_number_i64(), Some(-3));
+        assert_eq!(
+            MutableValue::Int64(i64::MAX).as_number_i64(),
+            Some(i64::MAX)
+        );
+        assert_eq!(MutableValue::Double(-4.0).as_number_i64(), Some(-4));
+        assert_eq!(MutableValue::Double(4.5).as_number_i64(), None);
+        assert_eq!(MutableValue::Double(f64::NAN).as_number_i64(), None);
+        assert_eq!(MutableValue::Double(f64::INFINITY).as_number_i64(), None);
+        assert_eq!(MutableValue::Double(1e19).as_number_i64(), None);
+        assert_eq!(MutableValue::Boolean(true).as_number_i64(), None);
+    }
+
+    #[test]
+    fn accessors() {
+        let raw = to_raw_document_buf(&doc! {
+            "code": Bson::JavaScriptCode("x = 1".into()),
+            "sym": Bson::Symbol("s".into()),
+            "dec": Decimal128::from_bytes([1; 16]),
+            "cws": JavaScriptCodeWithScope { code: "y".into(), scope: doc! {} },
+        })
+        .unwrap();
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(doc["code"].as_javascript_code(), Some("x = 1"));
+        assert_eq!(doc["sym"].as_symbol(), Some("s"));
+        assert_eq!(doc["sym"].as_javascript_code(), None);
+        assert_eq!(
+            doc["dec"].as_decimal128(),
+            Some(Decimal128::from_bytes([1; 16]))
+        );
+        assert!(doc["cws"].as_javascript_with_scope().is_some());
+        assert!(doc["code"].as_javascript_with_scope().is_none());
+        doc.get_mut("cws")
+            .and_then(MutableValue::as_javascript_with_scope_mut)
+            .unwrap()
+            .to_parsed()
+            .unwrap()
+            .insert("z", 1);
+        assert!(matches!(
+            doc["cws"],
+            MutableValue::JavaScriptCodeWithScope(MutableJavaScriptCodeWithScope::Parsed { .. })
+        ));
+    }
+
+    #[test]
+    fn replace_with() {
+        let mut value = MutableValue::from("x");
+        value.replace_with(|v| vec![v, MutableValue::Null].into());
+        assert_eq!(
+            value,
+            MutableValue::from(vec![MutableValue::from("x"), MutableValue::Null])
+        );
+    }
+
+    #[test]
+    fn type_name() {
+        assert_eq!(MutableValue::Double(1.0).type_name(), "Double");
+        assert_eq!(
+            MutableValue::from(ParsedDocument::new()).type_name(),
+            "Document"
+        );
+        assert_eq!(MutableValue::MaxKey.type_name(), "MaxKey");
+
+        assert!(MutableValue::Null.is_null());
+        assert!(MutableValue::Undefined.is_undefined());
+        assert!(MutableValue::MinKey.is_min_key());
+        assert!(MutableValue::MaxKey.is_max_key());
+        assert!(!MutableValue::Undefined.is_null());
+        assert!(!MutableValue::MaxKey.is_min_key());
+    }
+
+    #[test]
+    fn array_sort() {
+        let raw = rawdoc! { "a": [3, "b", 1.5, null, "a", 2i64] };
+        let mut array = MutableArray::from(raw.get_array("a").unwrap());
+        array.sort_by(compare_values).unwrap();
+        assert_eq!(
+            array.to_vec().unwrap(),
+            rawdoc! { "0": null, "1": 1.5, "2": 2i64, "3": 3, "4": "a", "5": "b" }.as_bytes()
+        );
+
+        array
+            .sort_by_key(|v| std::cmp::Reverse(v.type_name()))
+            .unwrap();
+        assert_eq!(
+            array.to_vec().unwrap(),
+            rawdoc! { "0": "a", "1": "b", "2": null, "3": 2i64, "4": 3, "5": 1.5 }.as_bytes()
+        );
+
+        array.sort_unstable_by(|a, b| compare_values(b, a)).unwrap();
+        assert_eq!(array.get(0).unwrap().unwrap().as_str(), Some("b"));
+    }
+
+    #[test]
+    fn binary_accessors() {
+        let raw = rawdoc! { "b": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2] } };
+        let mut binary = MutableBinary::from(raw.get_binary("b").unwrap());
+        assert_eq!(binary.bytes(), &[1, 2]);
+        assert_eq!(binary.subtype(), BinarySubtype::Generic);
+        binary.set_subtype(BinarySubtype::Md5);
+        assert!(matches!(binary, MutableBinary::Owned(_)));
+        assert_eq!(binary.bytes(), &[1, 2]);
+        binary.set_bytes([3u8; 3]);
+        assert_eq!(
+            binary.into_binary(),
+            Binary {
+                subtype: BinarySubtype::Md5,
+                bytes: vec![3, 3, 3]
+            }
+        );
+    }
+
+    #[test]
+    fn binary_new() {
+        let binary = MutableBinary::new(&[1u8, 2][..], BinarySubtype::Md5);
+        assert_eq!(binary.bytes(), &[1, 2]);
+        assert_eq!(binary.subtype(), BinarySubtype::Md5);
+        let binary = MutableBinary::new_generic(vec![3]);
+        assert_eq!(
+            binary.into_binary(),
+            Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: vec![3]
+            }
+        );
+    }
+
+    #[test]
+    fn binary_bytes_mut() {
+        let raw = rawdoc! { "b": Binary { subtype: BinarySubtype::Md5, bytes: vec![1, 2] } };
+        let mut binary = MutableBinary::from(raw.get_binary("b").unwrap());
+        binary.bytes_mut().push(3);
+        assert!(matches!(binary, MutableBinary::Owned(_)));
+        binary.bytes_mut()[0] = 0;
+        assert_eq!(binary.bytes(), &[0, 2, 3]);
+        assert_eq!(binary.subtype(), BinarySubtype::Md5);
+    }
+
+    #[test]
+    fn regex_accessors() {
+        let raw = rawdoc! { "r": Regex { pattern: "a.*".into(), options: "i".into() } };
+        let mut regex = MutableRegex::from(raw.get_regex("r").unwrap());
+        assert_eq!(regex.pattern(), "a.*");
+        assert_eq!(regex.options(), "i");
+        regex.set_pattern("b+");
+        assert!(matches!(regex, MutableRegex::Owned(_)));
+        assert_eq!(regex.options(), "i");
+        regex.set_options("mx");
+        assert_eq!(regex.pattern(), "b+");
+        assert_eq!(regex.options(), "mx");
+    }
+
+    #[test]
+    fn regex_new() {
+        let regex = MutableRegex::new("a.*", "xi").unwrap();
+        assert!(matches!(regex, MutableRegex::Owned(_)));
+        assert_eq!(regex.pattern(), "a.*");
+        assert_eq!(regex.options(), "ix");
+        assert_eq!(
+            MutableRegex::new("a\0", "").unwrap_err(),
+            InvalidRegexError::NullByte
+        );
+        assert_eq!(
+            MutableRegex::new("a", "i\0").unwrap_err(),
+            InvalidRegexError::NullByte
+        );
+        assert_eq!(
+            MutableRegex::new("a", "iq").unwrap_err(),
+            InvalidRegexError::InvalidOption('q')
+        );
+    }
+
+    #[test]
+    fn binary_eq() {
+        let raw = rawdoc! { "b": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] } };
+        let borrowed = MutableBinary::from(raw.get_binary("b").unwrap());
+        assert_eq!(
+            borrowed,
+            MutableBinary::from(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: vec![1, 2, 3]
+            })
+        );
+        assert_ne!(
+            borrowed,
+            MutableBinary::from(Binary {
+                subtype: BinarySubtype::UserDefined(0x80),
+                bytes: vec![1, 2, 3]
+            })
+        );
+    }
+
+    #[test]
+    fn regex_eq() {
+        let raw = rawdoc! { "r": Regex { pattern: "foo.*".into(), options: "i".into() } };
+        let borrowed = MutableRegex::from(raw.get_regex("r").unwrap());
+        assert_eq!(
+            borrowed,
+            MutableRegex::from(Regex {
+                pattern: "foo.*".into(),
+                options: "i".into()
+            })
+        );
+        assert_ne!(
+            borrowed,
+            MutableRegex::from(Regex {
+                pattern: "foo.*".into(),
+                options: "m".into()
+            })
+        );
+    }
 }
@@ -0,0 +1,114 @@
+/// Construct a [`ParsedDocument`](crate::ParsedDocument) from a JSON-like literal.
+///
+/// Values may be any expression that implements `Into<MutableValue<'static>>`. Nested `{ ... }`
+/// literals become documents and `[ ... ]` literals become arrays.
+///
+/// ```
+/// use mutable_bson::parsed_doc;
+///
+/// let doc = parsed_doc! {
+///     "name": "widget",
+///     "count": 2 + 3,
+///     "dims": { "w": 1.5, "h": 2.0 },
+///     "tags": ["a", "b", { "c": true }],
+/// };
+/// assert_eq!(doc.get_i32("count"), Some(5));
+/// ```
+#[macro_export]
+macro_rules! parsed_doc {
+    (@doc $doc:ident) => {};
+    (@doc $doc:ident , $($rest:tt)*) => {
+        $crate::parsed_doc!(@doc $doc $($rest)*);
+    };
+    (@doc $doc:ident $key:literal : { $($inner:tt)* } $($rest:tt)*) => {
+        $doc.insert($key, $crate::parsed_doc! { $($inner)* });
+        $crate::parsed_doc!(@doc $doc $($rest)*);
+    };
+    (@doc $doc:ident $key:literal : [ $($inner:tt)* ] $($rest:tt)*) => {
+        $doc.insert($key, $crate::parsed_doc!(@array [ $($inner)* ]));
+        $crate::parsed_doc!(@doc $doc $($rest)*);
+    };
+    (@doc $doc:ident $key:literal : $value:expr , $($rest:tt)*) => {
+        $doc.insert($key, $value);
+        $crate::parsed_doc!(@doc $doc $($rest)*);
+    };
+    (@doc $doc:ident $key:literal : $value:expr) => {
+        $doc.insert($key, $value);
+    };
+
+    (@elems [ $($done:expr,)* ]) => {{
+        let vec: ::std::vec::Vec<$crate::MutableValue<'static>> = ::std::vec![$($done),*];
+        vec
+    }};
+    (@elems [ $($done:expr,)* ] , $($rest:tt)*) => {
+        $crate::parsed_doc!(@elems [ $($done,)* ] $($rest)*)
+    };
+    (@elems [ $($done:expr,)* ] { $($inner:tt)* } $($rest:tt)*) => {
+        $crate::parsed_doc!(
+            @elems [ $($done,)* $crate::MutableValue::from($crate::parsed_doc! { $($inner)* }), ]
+            $($rest)*
+        )
+    };
+    (@elems [ $($done:expr,)* ] [ $($inner:tt)* ] $($rest:tt)*) => {
+        $crate::parsed_doc!(
+            @elems [ $($done,)* $crate::MutableValue::from($crate::parsed_doc!(@array [ $($inner)* ])), ]
+            $($rest)*
+        )
+    };
+    (@elems [ $($done:expr,)* ] $value:expr , $($rest:tt)*) => {
+        $crate::parsed_doc!(@elems [ $($done,)* $crate::MutableValue::from($value), ] $($rest)*)
+    };
+    (@elems [ $($done:expr,)* ] $value:expr) => {
+        $crate::parsed_doc!(@elems [ $($done,)* $crate::MutableValue::from($value), ])
+    };
+
+    (@array [ $($elems:tt)* ]) => {
+        $crate::parsed_doc!(@elems [] $($elems)*)
+    };
+
+    ($($fields:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut doc = $crate::ParsedDocument::new();
+        $crate::parsed_doc!(@doc doc $($fields)*);
+        doc
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{Bson, Document, doc};
+
+    use crate::{MutableValue, ParsedDocument};
+
+    #[test]
+    fn empty() {
+        let doc: ParsedDocument<'static> = parsed_doc! {};
+        assert!(doc.is_empty());
+    }
+
+    #[test]
+    fn nested() {
+        let name = String::from("widget");
+        let doc = parsed_doc! {
+            "name": name,
+            "count": 2 + 3,
+            "neg": -1i64,
+            "null": MutableValue::Null,
+            "dims": { "w": 1.5, "h": { "x": true } },
+            "tags": ["a", 7, { "c": false }, [], ["b"]],
+            "empty": {},
+        };
+        assert_eq!(
+            doc.try_into_document().unwrap(),
+            doc! {
+                "name": "widget",
+                "count": 5,
+                "neg": -1i64,
+                "null": Bson::Null,
+                "dims": { "w": 1.5, "h": { "x": true } },
+                "tags": ["a", 7, { "c": false }, [], ["b"]],
+                "empty": Document::new(),
+            }
+        );
+    }
+}
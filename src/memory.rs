@@ -0,0 +1,175 @@
+use std::{borrow::Cow, mem::size_of};
+
+use crate::{
+    MutableArray, MutableBinary, MutableDbPointer, MutableDocument, MutableJavaScriptCodeWithScope,
+    MutableRegex, MutableValue, ParsedDocument,
+};
+
+// The Cow variant determines whether the string is allocated.
+#[allow(clippy::ptr_arg)]
+fn cow_str_size(s: &Cow<'_, str>) -> usize {
+    match s {
+        Cow::Borrowed(_) => 0,
+        Cow::Owned(s) => s.capacity(),
+    }
+}
+
+impl MutableValue<'_> {
+    /// Returns an estimate of the heap memory owned by this value in bytes, not including the
+    /// size of the value itself.
+    ///
+    /// Values that refer to encoded BSON do not own the memory they point to and contribute 0.
+    pub fn memory_size(&self) -> usize {
+        match self {
+            Self::String(v) | Self::JavaScriptCode(v) | Self::Symbol(v) => cow_str_size(v),
+            Self::Document(v) => v.memory_size(),
+            Self::Array(v) => v.memory_size(),
+            Self::Binary(v) => v.memory_size(),
+            Self::RegularExpression(v) => v.memory_size(),
+            Self::DbPointer(v) => v.memory_size(),
+            Self::JavaScriptCodeWithScope(v) => v.memory_size(),
+            _ => 0,
+        }
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Returns an estimate of the heap memory owned by this document in bytes.
+    ///
+    /// See [`MutableValue::memory_size`].
+    pub fn memory_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_) => 0,
+            Self::Owned(p) => p.memory_size(),
+        }
+    }
+}
+
+impl ParsedDocument<'_> {
+    /// Returns an estimate of the heap memory owned by this document in bytes, not including the
+    /// size of the document itself.
+    ///
+    /// See [`MutableValue::memory_size`].
+    pub fn memory_size(&self) -> usize {
+        // Each entry is stored with its hash in the entry vector and indexed from the hash table.
+        let entry_size =
+            size_of::<Cow<'_, str>>() + size_of::<MutableValue<'_>>() + 2 * size_of::<usize>();
+        self.capacity() * entry_size
+            + self
+                .fields_iter()
+                .map(|(k, v)| cow_str_size(k) + v.memory_size())
+                .sum::<usize>()
+    }
+}
+
+impl MutableArray<'_> {
+    /// Returns an estimate of the heap memory owned by this array in bytes.
+    ///
+    /// See [`MutableValue::memory_size`].
+    pub fn memory_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_) => 0,
+            Self::Owned(v) => {
+                v.capacity() * size_of::<MutableValue<'_>>()
+                    + v.iter().map(MutableValue::memory_size).sum::<usize>()
+            }
+        }
+    }
+}
+
+impl MutableBinary<'_> {
+    fn memory_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_) => 0,
+            Self::Owned(v) => v.bytes.capacity(),
+        }
+    }
+}
+
+impl MutableRegex<'_> {
+    fn memory_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_) => 0,
+            Self::Owned(v) => v.pattern.capacity() + v.options.capacity(),
+        }
+    }
+}
+
+impl MutableDbPointer<'_> {
+    fn memory_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_) => 0,
+            // The namespace is the only heap allocation; it is encoded as a string (length prefix
+            // and null terminator) followed by a 12 byte ObjectId.
+            Self::Owned(_) => self.encode().len() - 17,
+        }
+    }
+}
+
+impl MutableJavaScriptCodeWithScope<'_> {
+    fn memory_size(&self) -> usize {
+        match self {
+            Self::Borrowed(_) => 0,
+            // bson::Document does not expose its allocation so estimate using the encoded size.
+            Self::Owned(v) => v.code.capacity() + self.raw_len() - 4 - (4 + v.code.len() + 1),
+            Self::Parsed { code, scope } => cow_str_size(code) + scope.memory_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{borrow::Cow, mem::size_of};
+
+    use bson::{Binary, rawdoc, spec::BinarySubtype};
+
+    use crate::{MutableArray, MutableBinary, MutableDocument, MutableValue, ParsedDocument};
+
+    #[test]
+    fn borrowed_is_zero() {
+        let raw = rawdoc! { "a": "xyz", "b": { "c": [1, 2] } };
+        assert_eq!(
+            MutableValue::Document(MutableDocument::from(raw.as_ref())).memory_size(),
+            0
+        );
+        assert_eq!(MutableValue::String(Cow::Borrowed("abc")).memory_size(), 0);
+        assert_eq!(MutableValue::Int64(7).memory_size(), 0);
+    }
+
+    #[test]
+    fn owned() {
+        let s = String::with_capacity(10);
+        assert_eq!(MutableValue::String(Cow::Owned(s)).memory_size(), 10);
+
+        let bytes = Vec::with_capacity(5);
+        let binary = MutableBinary::Owned(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes,
+        });
+        assert_eq!(MutableValue::Binary(binary).memory_size(), 5);
+
+        let mut values = Vec::with_capacity(4);
+        values.push(MutableValue::from("abc"));
+        let array = MutableArray::Owned(values);
+        assert_eq!(array.memory_size(), 4 * size_of::<MutableValue<'_>>() + 3);
+
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", "abcdef");
+        let cloned = array.clone();
+        let cloned_size = cloned.memory_size();
+        assert_eq!(cloned_size, size_of::<MutableValue<'_>>() + 3);
+        doc.insert("b", MutableValue::Array(cloned));
+        let entry_size =
+            size_of::<Cow<'_, str>>() + size_of::<MutableValue<'_>>() + 2 * size_of::<usize>();
+        assert_eq!(
+            doc.memory_size(),
+            doc.capacity() * entry_size + 1 + 6 + 1 + cloned_size
+        );
+
+        let raw = rawdoc! { "a": "xyz" };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.memory_size(), 0);
+        doc.to_parsed().unwrap();
+        assert!(doc.memory_size() > 0);
+    }
+}
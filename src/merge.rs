@@ -0,0 +1,269 @@
+use crate::{MutableDocument, MutableValue, ParsedDocument};
+
+/// How to resolve a key that appears in both documents when merging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Replace the existing value with the incoming value.
+    Overwrite,
+    /// Keep the existing value.
+    Skip,
+    /// Fail without modifying the document.
+    Error,
+}
+
+/// A key appeared in both documents when merging with [`ConflictPolicy::Error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictError(pub String);
+
+impl std::fmt::Display for ConflictError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "key {} exists in both documents", self.0)
+    }
+}
+
+impl std::error::Error for ConflictError {}
+
+impl<'a> ParsedDocument<'a> {
+    /// Copy all entries from other into this document, resolving keys present in both documents
+    /// according to conflict.
+    ///
+    /// New keys are appended in the order they appear in other, and overwritten values keep their
+    /// position in this document.
+    pub fn merge_from(
+        &mut self,
+        other: &ParsedDocument<'a>,
+        conflict: ConflictPolicy,
+    ) -> Result<(), ConflictError> {
+        if conflict == ConflictPolicy::Error
+            && let Some(key) = other.keys().find(|k| self.contains_key(k))
+        {
+            return Err(ConflictError(key.to_string()));
+        }
+        self.extend(
+            other
+                .iter()
+                .filter(|(k, _)| conflict == ConflictPolicy::Overwrite || !self.contains_key(k))
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect::<Vec<_>>(),
+        );
+        Ok(())
+    }
+}
+
+/// How to combine two documents with [`MutableDocument::merge()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Values in the other document replace values for the same key.
+    Shallow,
+    /// Documents present under the same key are merged recursively, and arrays are combined
+    /// according to the array strategy. Other values in the other document replace values for
+    /// the same key.
+    Deep(ArrayStrategy),
+    /// Fail if a key is present in both documents with values of different types; otherwise
+    /// values in the other document replace values for the same key.
+    ErrorOnConflict,
+}
+
+/// How to combine arrays present under the same key with [`MergeStrategy::Deep`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrayStrategy {
+    /// Use the array from the other document.
+    Replace,
+    /// Append the elements of the other array.
+    Append,
+    /// Insert the elements of the other array before the existing elements.
+    Prepend,
+}
+
+/// Errors that may occur when merging documents with [`MutableDocument::merge()`].
+#[derive(Clone, Debug)]
+pub enum MergeError {
+    /// A document or array could not be parsed.
+    Raw(bson::raw::Error),
+    /// The dot-notation path has values of different types in each document.
+    Conflict(String),
+}
+
+impl std::fmt::Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw(e) => write!(f, "{}", e),
+            Self::Conflict(p) => write!(f, "values at {} have different types", p),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+impl From<bson::raw::Error> for MergeError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Returns a new document containing the entries of this document combined with the entries
+    /// of other according to strategy.
+    ///
+    /// Keys only present in other are appended in the order they appear in other, and replaced or
+    /// merged values keep their position in this document. Neither input is modified.
+    pub fn merge(
+        &self,
+        other: &MutableDocument<'_>,
+        strategy: MergeStrategy,
+    ) -> Result<ParsedDocument<'static>, MergeError> {
+        merge_documents(self, other, strategy, None)
+    }
+}
+
+fn merge_documents(
+    doc: &MutableDocument<'_>,
+    other: &MutableDocument<'_>,
+    strategy: MergeStrategy,
+    prefix: Option<&str>,
+) -> Result<ParsedDocument<'static>, MergeError> {
+    let mut merged = doc
+        .iter()
+        .map(|e| {
+            let (k, v) = e?;
+            Ok((k.to_string(), v.into_owned().into_owned()?))
+        })
+        .collect::<Result<ParsedDocument<'static>, bson::raw::Error>>()?;
+    for e in other.iter() {
+        let (k, v) = e?;
+        let v = v.into_owned().into_owned()?;
+        let Some(existing) = merged.get_mut(k) else {
+            merged.insert(k, v);
+            continue;
+        };
+        let path = match prefix {
+            Some(p) => format!("{}.{}", p, k),
+            None => k.to_string(),
+        };
+        *existing = merge_values(existing, v, strategy, &path)?;
+    }
+    Ok(merged)
+}
+
+fn merge_values(
+    existing: &MutableValue<'static>,
+    value: MutableValue<'static>,
+    strategy: MergeStrategy,
+    path: &str,
+) -> Result<MutableValue<'static>, MergeError> {
+    match (strategy, existing, value) {
+        (MergeStrategy::ErrorOnConflict, e, v) if e.element_type() != v.element_type() => {
+            Err(MergeError::Conflict(path.to_string()))
+        }
+        (MergeStrategy::Deep(_), MutableValue::Document(a), MutableValue::Document(b)) => Ok(
+            MutableValue::Document(merge_documents(a, &b, strategy, Some(path))?.into()),
+        ),
+        (MergeStrategy::Deep(arrays), MutableValue::Array(a), MutableValue::Array(b)) => {
+            let (mut first, mut second) = match arrays {
+                ArrayStrategy::Replace => return Ok(MutableValue::Array(b)),
+                ArrayStrategy::Append => (a.clone(), b),
+                ArrayStrategy::Prepend => (b, a.clone()),
+            };
+            first.append(&mut second)?;
+            Ok(MutableValue::Array(first))
+        }
+        (_, _, v) => Ok(v),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::MutableDocument;
+
+    use super::{ArrayStrategy, ConflictError, ConflictPolicy, MergeError, MergeStrategy};
+
+    #[test]
+    fn merge_from() {
+        let defaults = parsed_doc! { "a": 1, "b": 2 };
+
+        let mut doc = parsed_doc! { "b": 3, "c": 4 };
+        doc.merge_from(&defaults, ConflictPolicy::Overwrite)
+            .unwrap();
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "b": 2, "c": 4, "a": 1 }.as_bytes()
+        );
+
+        let mut doc = parsed_doc! { "b": 3, "c": 4 };
+        doc.merge_from(&defaults, ConflictPolicy::Skip).unwrap();
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "b": 3, "c": 4, "a": 1 }.as_bytes()
+        );
+
+        let mut doc = parsed_doc! { "b": 3, "c": 4 };
+        assert_eq!(
+            doc.merge_from(&defaults, ConflictPolicy::Error),
+            Err(ConflictError("b".to_string()))
+        );
+        assert_eq!(doc.to_vec().unwrap(), rawdoc! { "b": 3, "c": 4 }.as_bytes());
+
+        let mut doc = parsed_doc! { "c": 4 };
+        doc.merge_from(&defaults, ConflictPolicy::Error).unwrap();
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "c": 4, "a": 1, "b": 2 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn merge_shallow() {
+        let base = rawdoc! { "a": 1, "b": { "c": 1, "d": 2 } };
+        let layer = rawdoc! { "b": { "c": 3 }, "e": 4 };
+        let merged = MutableDocument::from(base.as_ref())
+            .merge(&layer.as_ref().into(), MergeStrategy::Shallow)
+            .unwrap();
+        assert_eq!(
+            merged.to_vec().unwrap(),
+            rawdoc! { "a": 1, "b": { "c": 3 }, "e": 4 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn merge_deep() {
+        let base = rawdoc! { "a": [1, 2], "b": { "c": 1, "d": { "e": 2 } }, "f": 1 };
+        let layer = rawdoc! { "a": [3], "b": { "d": { "g": 3 }, "c": "x" }, "f": { "h": 1 } };
+        let base = MutableDocument::from(base.as_ref());
+        let layer = MutableDocument::from(layer.as_ref());
+        for (arrays, expected) in [
+            (ArrayStrategy::Replace, rawdoc! { "a": [3] }),
+            (ArrayStrategy::Append, rawdoc! { "a": [1, 2, 3] }),
+            (ArrayStrategy::Prepend, rawdoc! { "a": [3, 1, 2] }),
+        ] {
+            let merged = base.merge(&layer, MergeStrategy::Deep(arrays)).unwrap();
+            let mut expected = MutableDocument::from(expected.as_ref());
+            expected
+                .insert("b", parsed_doc! { "c": "x", "d": { "e": 2, "g": 3 } })
+                .unwrap();
+            expected.insert("f", parsed_doc! { "h": 1 }).unwrap();
+            assert_eq!(merged.to_vec().unwrap(), expected.to_vec().unwrap());
+        }
+    }
+
+    #[test]
+    fn merge_error_on_conflict() {
+        let base = rawdoc! { "a": 1, "b": { "c": 1 } };
+        let same_types = rawdoc! { "a": 2, "b": { "d": 1 } };
+        let merged = MutableDocument::from(base.as_ref())
+            .merge(&same_types.as_ref().into(), MergeStrategy::ErrorOnConflict)
+            .unwrap();
+        assert_eq!(
+            merged.to_vec().unwrap(),
+            rawdoc! { "a": 2, "b": { "d": 1 } }.as_bytes()
+        );
+
+        let different_types = rawdoc! { "a": "x" };
+        assert!(matches!(
+            MutableDocument::from(base.as_ref())
+                .merge(&different_types.as_ref().into(), MergeStrategy::ErrorOnConflict),
+            Err(MergeError::Conflict(p)) if p == "a"
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    MutableArray, MutableDocument, MutableValue, ParsedDocument, SerializeOptions,
+    write::invalid_data_ser_error,
+};
+
+/// Errors that may occur when validating document nesting depth.
+#[derive(Clone, Debug)]
+pub enum NestingError {
+    /// A document or array could not be parsed.
+    Raw(bson::raw::Error),
+    /// Documents and arrays are nested more deeply than this limit.
+    TooDeep(usize),
+}
+
+impl std::fmt::Display for NestingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw(e) => write!(f, "{}", e),
+            Self::TooDeep(d) => write!(f, "exceeded max nesting depth {}", d),
+        }
+    }
+}
+
+impl std::error::Error for NestingError {}
+
+impl From<bson::raw::Error> for NestingError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Returns the maximum depth of nested documents and arrays, where a document with no nested
+    /// documents or arrays has depth 1.
+    ///
+    /// Encoded documents are scanned without parsing, and are truncated at the first malformed
+    /// element.
+    pub fn max_nesting_depth(&self) -> usize {
+        1 + self
+            .iter()
+            .map_while(Result::ok)
+            .map(|(_, v)| value_depth(&v))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Check that documents and arrays are nested no more than max_depth deep, as measured by
+    /// [`MutableDocument::max_nesting_depth`].
+    ///
+    /// Stops at the first value that exceeds the limit. May fail with a raw BSON parsing error.
+    pub fn validate_nesting(&self, max_depth: usize) -> Result<(), NestingError> {
+        check_document(self, max_depth, max_depth)
+    }
+}
+
+impl ParsedDocument<'_> {
+    /// Returns the maximum depth of nested documents and arrays, where a document with no nested
+    /// documents or arrays has depth 1.
+    ///
+    /// Encoded documents are truncated at the first malformed element.
+    pub fn max_nesting_depth(&self) -> usize {
+        1 + self.values().map(value_depth).max().unwrap_or(0)
+    }
+
+    /// Check that documents and arrays are nested no more than max_depth deep, as measured by
+    /// [`ParsedDocument::max_nesting_depth`].
+    ///
+    /// Stops at the first value that exceeds the limit. May fail with a raw BSON parsing error.
+    pub fn validate_nesting(&self, max_depth: usize) -> Result<(), NestingError> {
+        if max_depth == 0 {
+            return Err(NestingError::TooDeep(max_depth));
+        }
+        for v in self.values() {
+            check_value(v, max_depth - 1, max_depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check the nesting depth limit in options before encoding a document.
+pub(crate) fn check_serialize_nesting(
+    validate: impl FnOnce(usize) -> Result<(), NestingError>,
+    options: &SerializeOptions,
+) -> Result<(), bson::ser::Error> {
+    match options.max_nesting_depth {
+        Some(max_depth) => validate(max_depth).map_err(invalid_data_ser_error),
+        None => Ok(()),
+    }
+}
+
+fn value_depth(value: &MutableValue<'_>) -> usize {
+    match value {
+        MutableValue::Document(d) => d.max_nesting_depth(),
+        MutableValue::Array(a) => array_depth(a),
+        _ => 0,
+    }
+}
+
+fn array_depth(array: &MutableArray<'_>) -> usize {
+    1 + array
+        .iter()
+        .map_while(Result::ok)
+        .map(|v| value_depth(&v))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Check a document when remaining levels of nesting are allowed, including the document.
+fn check_document(
+    doc: &MutableDocument<'_>,
+    remaining: usize,
+    max_depth: usize,
+) -> Result<(), NestingError> {
+    if remaining == 0 {
+        return Err(NestingError::TooDeep(max_depth));
+    }
+    for e in doc.iter() {
+        check_value(e?.1.as_ref(), remaining - 1, max_depth)?;
+    }
+    Ok(())
+}
+
+fn check_value(
+    value: &MutableValue<'_>,
+    remaining: usize,
+    max_depth: usize,
+) -> Result<(), NestingError> {
+    match value {
+        MutableValue::Document(d) => check_document(d, remaining, max_depth),
+        MutableValue::Array(a) => {
+            if remaining == 0 {
+                return Err(NestingError::TooDeep(max_depth));
+            }
+            for v in a.iter() {
+                check_value(v?.as_ref(), remaining - 1, max_depth)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableDocument, ParsedDocument, SerializeOptions};
+
+    use super::NestingError;
+
+    #[test]
+    fn max_nesting_depth() {
+        assert_eq!(ParsedDocument::new().max_nesting_depth(), 1);
+        let raw = rawdoc! { "a": 1, "b": { "c": [1, { "d": [] }] }, "e": {} };
+        let doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.max_nesting_depth(), 5);
+        assert_eq!(
+            ParsedDocument::try_from(raw.as_ref())
+                .unwrap()
+                .max_nesting_depth(),
+            5
+        );
+    }
+
+    #[test]
+    fn validate_nesting() {
+        let raw = rawdoc! { "a": 1, "b": { "c": [1, { "d": [] }] }, "e": {} };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        for _ in 0..2 {
+            assert!(doc.validate_nesting(5).is_ok());
+            assert!(matches!(
+                doc.validate_nesting(4),
+                Err(NestingError::TooDeep(4))
+            ));
+            doc.to_parsed().unwrap();
+        }
+        assert!(matches!(
+            ParsedDocument::new().validate_nesting(0),
+            Err(NestingError::TooDeep(0))
+        ));
+    }
+
+    #[test]
+    fn to_vec_with_nesting_limit() {
+        let raw = rawdoc! { "a": { "b": { "c": 1 } } };
+        let doc = MutableDocument::from(raw.as_ref());
+        let limit = |n| SerializeOptions {
+            max_nesting_depth: Some(n),
+            ..SerializeOptions::default()
+        };
+        assert_eq!(doc.to_vec_with_options(&limit(3)).unwrap(), raw.as_bytes());
+        assert!(doc.to_vec_with_options(&limit(2)).is_err());
+        let parsed = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert!(parsed.to_vec_with_options(&limit(3)).is_ok());
+        assert!(parsed.to_vec_with_options(&limit(2)).is_err());
+        assert!(doc.to_vec().is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+use std::borrow::Cow;
+
+use bson::RawDocument;
+
+use crate::{MutableValue, ParsedDocument, write::invalid_data_ser_error};
+
+/// A read-only view of an encoded document with some fields replaced or added.
+///
+/// Fields in the overrides shadow fields with the same key in the base document. Iteration yields
+/// all override fields in order followed by the base fields that are not overridden, so the
+/// merged document is never materialized unless it is serialized. Create one with [`overlay()`].
+#[derive(Clone, Debug)]
+pub struct OverlayDocument<'a> {
+    base: &'a RawDocument,
+    overrides: ParsedDocument<'a>,
+}
+
+/// Create an [`OverlayDocument`] that reads fields from overrides before base.
+pub fn overlay<'a>(base: &'a RawDocument, overrides: ParsedDocument<'a>) -> OverlayDocument<'a> {
+    OverlayDocument { base, overrides }
+}
+
+impl<'a> OverlayDocument<'a> {
+    /// Returns the encoded document that fields are read from when they are not overridden.
+    pub fn base(&self) -> &'a RawDocument {
+        self.base
+    }
+
+    /// Returns the fields that replace or are added to the base document.
+    pub fn overrides(&self) -> &ParsedDocument<'a> {
+        &self.overrides
+    }
+
+    /// Get the value for key from the overrides, or from the base document if not overridden.
+    ///
+    /// Looking up a key in the base document runs in _O(n)_ time and may fail with a raw BSON
+    /// parsing error.
+    pub fn get(
+        &self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<Cow<'_, MutableValue<'a>>>, bson::raw::Error> {
+        let key = key.as_ref();
+        match self.overrides.get(key) {
+            Some(v) => Ok(Some(Cow::Borrowed(v))),
+            None => Ok(self.base.get(key)?.map(|v| Cow::Owned(v.into()))),
+        }
+    }
+
+    /// Returns true if key is in the overrides or the base document.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn contains_key(&self, key: impl AsRef<str>) -> Result<bool, bson::raw::Error> {
+        let key = key.as_ref();
+        Ok(self.overrides.contains_key(key) || self.base.get(key)?.is_some())
+    }
+
+    /// Iterate over the override fields followed by base fields that are not overridden.
+    ///
+    /// Base values are decoded as they are visited. Yields a raw BSON parsing error if the base
+    /// document is malformed.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = Result<(&str, Cow<'_, MutableValue<'a>>), bson::raw::Error>> {
+        self.overrides
+            .iter()
+            .map(|(k, v)| Ok((k, Cow::Borrowed(v))))
+            .chain(self.base.iter_elements().filter_map(|e| match e {
+                Ok(e) if self.overrides.contains_key(e.key()) => None,
+                Ok(e) => Some(e.value().map(|v| (e.key(), Cow::Owned(v.into())))),
+                Err(e) => Some(Err(e)),
+            }))
+    }
+
+    /// Produce an encoded raw document containing the merged fields in iteration order.
+    ///
+    /// Fails if the base document is malformed or the merged document cannot be encoded.
+    pub fn to_vec(&self) -> Result<Vec<u8>, bson::ser::Error> {
+        let mut merged = ParsedDocument::with_capacity(self.overrides.len());
+        for field in self.iter() {
+            let (k, v) = field.map_err(invalid_data_ser_error)?;
+            merged.insert_borrowed(k, v.into_owned());
+        }
+        merged.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableValue, ParsedDocument};
+
+    use super::overlay;
+
+    #[test]
+    fn get_and_contains_key() {
+        let base = rawdoc! { "a": 1, "b": "c" };
+        let doc = overlay(&base, parsed_doc! { "b": "d", "e": 2 });
+        assert_eq!(doc.get("a").unwrap().unwrap().as_i32(), Some(1));
+        assert_eq!(doc.get("b").unwrap().unwrap().as_str(), Some("d"));
+        assert_eq!(doc.get("e").unwrap().unwrap().as_i32(), Some(2));
+        assert!(doc.get("z").unwrap().is_none());
+        assert!(doc.contains_key("a").unwrap());
+        assert!(doc.contains_key("e").unwrap());
+        assert!(!doc.contains_key("z").unwrap());
+    }
+
+    #[test]
+    fn iter_and_to_vec() {
+        let base = rawdoc! { "a": 1, "b": "c", "f": { "g": true } };
+        let doc = overlay(&base, parsed_doc! { "e": 2, "b": "d" });
+        assert_eq!(
+            doc.iter()
+                .map(|f| {
+                    let (k, v) = f.unwrap();
+                    (k.to_string(), v.into_owned())
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                ("e".to_string(), MutableValue::from(2)),
+                ("b".to_string(), MutableValue::from("d")),
+                ("a".to_string(), MutableValue::from(1)),
+                (
+                    "f".to_string(),
+                    MutableValue::from(base.get("f").unwrap().unwrap())
+                ),
+            ]
+        );
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "e": 2, "b": "d", "a": 1, "f": { "g": true } }.as_bytes()
+        );
+        assert_eq!(
+            overlay(&base, ParsedDocument::new()).to_vec().unwrap(),
+            base.as_bytes()
+        );
+    }
+}
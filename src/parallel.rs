@@ -0,0 +1,88 @@
+use rayon::prelude::*;
+
+use crate::{
+    ParsedDocument, SerializeOptions, encode_to_vec, nesting::check_serialize_nesting,
+    put_raw_cstr, raw_cstr_len,
+};
+
+impl ParsedDocument<'_> {
+    /// Produce an encoded raw document like [`Self::to_vec()`], encoding top-level fields in
+    /// parallel.
+    ///
+    /// Each field is written to its own region of the output, located using the encoded length of
+    /// the preceding fields. This is only faster than [`Self::to_vec()`] for large documents with
+    /// many large fields.
+    pub fn to_vec_parallel(&self) -> Result<Vec<u8>, bson::ser::Error> {
+        self.to_vec_parallel_with_options(&SerializeOptions::default())
+    }
+
+    /// Produce an encoded raw document in parallel, failing if it exceeds the limits in options.
+    pub fn to_vec_parallel_with_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<Vec<u8>, bson::ser::Error> {
+        check_serialize_nesting(|d| self.validate_nesting(d), options)?;
+        let len = self.raw_len();
+        encode_to_vec(len, options, |buf| {
+            buf.resize(len, 0);
+            let (header, mut rest) = buf.split_at_mut(4);
+            header.copy_from_slice(
+                &i32::try_from(len)
+                    .expect("message len checked before put")
+                    .to_le_bytes(),
+            );
+
+            let mut regions = Vec::with_capacity(self.len());
+            for (k, v) in self.fields_iter() {
+                let (region, next) = rest.split_at_mut(1 + raw_cstr_len(k.as_ref()) + v.raw_len());
+                regions.push((region, k, v));
+                rest = next;
+            }
+            assert_eq!(rest.len(), 1, "only the document terminator remains");
+
+            regions.into_par_iter().try_for_each(|(mut region, k, v)| {
+                region[0] = v.element_type() as u8;
+                region = &mut region[1..];
+                put_raw_cstr(k.as_ref(), &mut region)?;
+                v.put(&mut region)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{Binary, rawdoc, spec::BinarySubtype};
+
+    use crate::{ParsedDocument, SerializeOptions};
+
+    #[test]
+    fn to_vec_parallel() {
+        let raw = rawdoc! {
+            "a": "x".repeat(1 << 16),
+            "b": { "c": [1, 2, { "d": 3.5 }] },
+            "e": Binary { subtype: BinarySubtype::Generic, bytes: vec![7; 1 << 16] },
+            "f": 4i64,
+        };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        doc.insert("g", "y");
+        assert_eq!(doc.to_vec_parallel().unwrap(), doc.to_vec().unwrap());
+        assert_eq!(
+            ParsedDocument::new().to_vec_parallel().unwrap(),
+            ParsedDocument::new().to_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn to_vec_parallel_too_large() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", "x".repeat(64));
+        assert!(
+            doc.to_vec_parallel_with_options(&SerializeOptions {
+                max_document_size: Some(32),
+                ..SerializeOptions::default()
+            })
+            .is_err()
+        );
+    }
+}
@@ -1,68 +1,394 @@
-use std::{borrow::Cow, ops::Index};
+use std::{borrow::Cow, ops::Index, sync::OnceLock};
 
-use bson::{Document, RawDocument};
+use bson::{
+    DateTime, Document, RawDocument, RawDocumentBuf,
+    oid::ObjectId,
+    spec::{BinarySubtype, ElementType},
+};
 use bytes::BufMut;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, map};
 
-use crate::{MutableValue, put_raw_cstr, raw_cstr_len};
+use crate::{
+    MutableArray, MutableBinary, MutableDocument, MutableValue, SerializeOptions, encode_to_vec,
+    nesting::check_serialize_nesting, put_raw_cstr, raw_cstr_len,
+};
 
 #[derive(Default, Clone, Debug)]
-pub struct ParsedDocument<'a>(IndexMap<Cow<'a, str>, MutableValue<'a>>);
+pub struct ParsedDocument<'a> {
+    fields: IndexMap<Cow<'a, str>, MutableValue<'a>>,
+    // Encoded length of the document, computed on demand and cleared by any method that may
+    // mutate the document.
+    cached_len: OnceLock<usize>,
+}
 
 impl<'a> ParsedDocument<'a> {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Create an empty document with space for at least capacity fields.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_fields(IndexMap::with_capacity(capacity))
+    }
+
+    fn with_fields(fields: IndexMap<Cow<'a, str>, MutableValue<'a>>) -> Self {
+        Self {
+            fields,
+            cached_len: OnceLock::new(),
+        }
+    }
+
+    /// Create a document from a map of fields.
+    pub fn from_indexmap(map: IndexMap<Cow<'a, str>, MutableValue<'a>>) -> Self {
+        Self::with_fields(map)
+    }
+
+    /// Returns the underlying map of fields.
+    pub fn as_indexmap(&self) -> &IndexMap<Cow<'a, str>, MutableValue<'a>> {
+        &self.fields
+    }
+
+    /// Returns the underlying map of fields for mutation.
+    pub fn as_indexmap_mut(&mut self) -> &mut IndexMap<Cow<'a, str>, MutableValue<'a>> {
+        self.fields_mut()
+    }
+
+    /// Consume this document and return the underlying map of fields.
+    pub fn into_indexmap(self) -> IndexMap<Cow<'a, str>, MutableValue<'a>> {
+        self.fields
+    }
+
+    /// Returns the fields for mutation, invalidating the cached length.
+    fn fields_mut(&mut self) -> &mut IndexMap<Cow<'a, str>, MutableValue<'a>> {
+        self.cached_len.take();
+        &mut self.fields
+    }
+
     pub fn insert<V: Into<MutableValue<'static>>>(
         &mut self,
         key: impl Into<String>,
         value: V,
-    ) -> Option<MutableValue<'_>> {
-        self.0.insert(Cow::from(key.into()), value.into())
+    ) -> Option<MutableValue<'a>> {
+        self.fields_mut()
+            .insert(Cow::from(key.into()), value.into())
+    }
+
+    /// Insert a value that may borrow from other encoded BSON for key and return the previous
+    /// value if any.
+    ///
+    /// Unlike [`ParsedDocument::insert`] this does not require the value to be `'static`, so
+    /// fields may be copied out of a [`RawDocument`] without copying the underlying bytes.
+    pub fn insert_borrowed<'b: 'a, V: Into<MutableValue<'b>>>(
+        &mut self,
+        key: impl Into<Cow<'a, str>>,
+        value: V,
+    ) -> Option<MutableValue<'a>> {
+        self.fields_mut().insert(key.into(), value.into())
     }
 
     /// Remove key and return the value for that key if present.
     ///
     /// Runs in _O(n)_ time.
     pub fn remove(&mut self, key: impl AsRef<str>) -> Option<MutableValue<'a>> {
-        self.0.shift_remove(key.as_ref())
+        self.fields_mut().shift_remove(key.as_ref())
+    }
+
+    /// Remove key and return the key and value if present.
+    ///
+    /// Runs in _O(n)_ time.
+    pub fn remove_entry(
+        &mut self,
+        key: impl AsRef<str>,
+    ) -> Option<(Cow<'a, str>, MutableValue<'a>)> {
+        self.fields_mut().shift_remove_entry(key.as_ref())
+    }
+
+    /// Retain only the entries for which f returns true, preserving the order of the document.
+    pub fn retain<F: FnMut(&str, &mut MutableValue<'a>) -> bool>(&mut self, mut f: F) {
+        self.fields_mut().retain(|k, v| f(k.as_ref(), v))
+    }
+
+    /// Rename the key old to new without changing the position of the entry.
+    ///
+    /// Fails if old does not exist or new already exists. Runs in _O(n)_ time.
+    pub fn rename_key(&mut self, old: &str, new: impl Into<String>) -> Result<(), RenameError> {
+        let new = new.into();
+        let index = self
+            .fields
+            .get_index_of(old)
+            .ok_or_else(|| RenameError::NotFound(old.to_string()))?;
+        if old == new {
+            return Ok(());
+        }
+        if self.fields.contains_key(new.as_str()) {
+            return Err(RenameError::AlreadyExists(new));
+        }
+        let fields = self.fields_mut();
+        let (_, value) = fields.shift_remove_index(index).expect("index is valid");
+        fields.shift_insert(index, Cow::from(new), value);
+        Ok(())
     }
 
     pub fn clear(&mut self) {
-        self.0.clear()
+        self.fields_mut().clear()
     }
 
     pub fn contains_key(&self, key: impl AsRef<str>) -> bool {
-        self.0.contains_key(key.as_ref())
+        self.fields.contains_key(key.as_ref())
+    }
+
+    /// Get the entry for key for in-place manipulation.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_, 'a> {
+        match self.fields_mut().entry(Cow::from(key.into())) {
+            map::Entry::Occupied(e) => Entry::Occupied(OccupiedEntry(e)),
+            map::Entry::Vacant(e) => Entry::Vacant(VacantEntry(e)),
+        }
+    }
+
+    /// Return a mutable reference to the value for key, inserting default if key is not present.
+    pub fn get_or_insert<V: Into<MutableValue<'static>>>(
+        &mut self,
+        key: impl Into<String>,
+        default: V,
+    ) -> &mut MutableValue<'a> {
+        self.entry(key).or_insert(default)
+    }
+
+    /// Return a mutable reference to the value for key, inserting the output of f if key is not
+    /// present.
+    pub fn get_or_insert_with<F: FnOnce() -> MutableValue<'static>>(
+        &mut self,
+        key: impl Into<String>,
+        f: F,
+    ) -> &mut MutableValue<'a> {
+        self.entry(key).or_insert_with(f)
     }
 
     pub fn get(&self, key: impl AsRef<str>) -> Option<&MutableValue<'a>> {
-        self.0.get(key.as_ref())
+        self.fields.get(key.as_ref())
     }
 
     pub fn get_mut(&mut self, key: impl AsRef<str>) -> Option<&mut MutableValue<'a>> {
-        self.0.get_mut(key.as_ref())
+        self.fields_mut().get_mut(key.as_ref())
+    }
+
+    /// Get the value for key if it is a string.
+    pub fn get_str(&self, key: impl AsRef<str>) -> Option<&str> {
+        self.get(key).and_then(MutableValue::as_str)
+    }
+
+    /// Get the value for key if it is a 32-bit integer.
+    pub fn get_i32(&self, key: impl AsRef<str>) -> Option<i32> {
+        self.get(key).and_then(MutableValue::as_i32)
+    }
+
+    /// Get the value for key if it is a 64-bit integer.
+    pub fn get_i64(&self, key: impl AsRef<str>) -> Option<i64> {
+        self.get(key).and_then(MutableValue::as_i64)
+    }
+
+    /// Get the value for key if it is a double.
+    pub fn get_f64(&self, key: impl AsRef<str>) -> Option<f64> {
+        self.get(key).and_then(MutableValue::as_f64)
+    }
+
+    /// Get the value for key if it is a boolean.
+    pub fn get_bool(&self, key: impl AsRef<str>) -> Option<bool> {
+        self.get(key).and_then(MutableValue::as_bool)
+    }
+
+    /// Get the value for key if it is an object id.
+    pub fn get_object_id(&self, key: impl AsRef<str>) -> Option<ObjectId> {
+        self.get(key).and_then(MutableValue::as_object_id)
+    }
+
+    /// Get the value for key if it is a datetime.
+    pub fn get_datetime(&self, key: impl AsRef<str>) -> Option<DateTime> {
+        self.get(key).and_then(MutableValue::as_date_time)
+    }
+
+    /// Get the bytes for key if it is binary with the generic subtype.
+    pub fn get_binary_generic(&self, key: impl AsRef<str>) -> Option<&[u8]> {
+        self.get(key)
+            .and_then(MutableValue::as_binary)
+            .map(MutableBinary::parts)
+            .and_then(|(bytes, subtype)| (subtype == BinarySubtype::Generic).then_some(bytes))
+    }
+
+    /// Get the value for key if it is a document.
+    pub fn get_document(&self, key: impl AsRef<str>) -> Option<&MutableDocument<'a>> {
+        self.get(key).and_then(MutableValue::as_doc)
+    }
+
+    /// Get the value for key if it is a document for mutation.
+    pub fn get_document_mut(&mut self, key: impl AsRef<str>) -> Option<&mut MutableDocument<'a>> {
+        self.get_mut(key).and_then(MutableValue::as_doc_mut)
+    }
+
+    /// Get the value for key if it is an array.
+    pub fn get_array(&self, key: impl AsRef<str>) -> Option<&MutableArray<'a>> {
+        self.get(key).and_then(MutableValue::as_array)
+    }
+
+    /// Get the value for key if it is an array for mutation.
+    pub fn get_array_mut(&mut self, key: impl AsRef<str>) -> Option<&mut MutableArray<'a>> {
+        self.get_mut(key).and_then(MutableValue::as_array_mut)
+    }
+
+    /// Get the key and value at index in insertion order.
+    pub fn get_index(&self, index: usize) -> Option<(&str, &MutableValue<'a>)> {
+        self.fields.get_index(index).map(|(k, v)| (k.as_ref(), v))
+    }
+
+    /// Get the key and a mutable value at index in insertion order.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&str, &mut MutableValue<'a>)> {
+        self.fields_mut()
+            .get_index_mut(index)
+            .map(|(k, v)| (k.as_ref(), v))
+    }
+
+    /// Returns the position of key in insertion order.
+    pub fn position_of(&self, key: &str) -> Option<usize> {
+        self.fields.get_index_of(key)
+    }
+
+    /// Swap the positions of the entries at indices a and b.
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        // Reordering entries does not change the encoded length.
+        self.fields.swap_indices(a, b)
+    }
+
+    /// Move the entry at index from to index to, shifting the entries in between.
+    ///
+    /// Panics if either index is out of bounds. Runs in _O(n)_ time.
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.fields.move_index(from, to)
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&str, &MutableValue<'a>)> {
-        self.0.iter().map(|(k, v)| (k.as_ref(), v))
+        self.fields.iter().map(|(k, v)| (k.as_ref(), v))
     }
 
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut MutableValue<'a>)> {
-        self.0.iter_mut().map(|(k, v)| (k.as_ref(), v))
+        self.fields_mut().iter_mut().map(|(k, v)| (k.as_ref(), v))
+    }
+
+    /// Iterate over the keys and value types of the document.
+    pub fn field_types(&self) -> impl Iterator<Item = (&str, ElementType)> {
+        self.fields
+            .iter()
+            .map(|(k, v)| (k.as_ref(), v.element_type()))
+    }
+
+    /// Iterate over the keys of the document in order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.fields.keys().map(|k| k.as_ref())
+    }
+
+    /// Iterate over the values of the document in key order.
+    pub fn values(&self) -> impl Iterator<Item = &MutableValue<'a>> {
+        self.fields.values()
+    }
+
+    /// Iterate over mutable references to the values of the document in key order.
+    ///
+    /// This invalidates the cached encoded length of the document.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut MutableValue<'a>> {
+        self.fields_mut().values_mut()
+    }
+
+    /// Replace every value with the output of f, which takes the existing value by move.
+    pub fn map_values<F: FnMut(MutableValue<'a>) -> MutableValue<'a>>(&mut self, mut f: F) {
+        for v in self.fields_mut().values_mut() {
+            v.replace_with(&mut f);
+        }
+    }
+
+    /// Consume this document and return a new document with each value replaced by the output
+    /// of f, preserving key order.
+    pub fn map_values_into_iter<F: FnMut(MutableValue<'a>) -> MutableValue<'a>>(
+        self,
+        mut f: F,
+    ) -> Self {
+        Self::with_fields(self.fields.into_iter().map(|(k, v)| (k, f(v))).collect())
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.fields.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.fields.is_empty()
+    }
+
+    /// Copy any borrowed keys and values so that the document has a `'static` lifetime.
+    ///
+    /// Any encoded documents or arrays are parsed, which may fail with a raw BSON parsing error.
+    pub fn into_owned(self) -> Result<ParsedDocument<'static>, bson::raw::Error> {
+        self.fields
+            .into_iter()
+            .map(|(k, v)| Ok((Cow::Owned(k.into_owned()), v.into_owned()?)))
+            .collect::<Result<IndexMap<_, _>, _>>()
+            .map(ParsedDocument::with_fields)
+    }
+
+    /// Capture the current state of the document so that it may be restored with
+    /// [`Self::rollback()`].
+    ///
+    /// This clones every entry, which runs in _O(n)_ time. Encoded documents and arrays are
+    /// copied by reference, so the cost is proportional to the number of parsed values rather
+    /// than the size of the document.
+    pub fn snapshot(&self) -> Snapshot<'a> {
+        Snapshot(self.clone())
+    }
+
+    /// Restore the document to the state captured by snapshot, discarding any changes made since.
+    pub fn rollback(&mut self, snapshot: Snapshot<'a>) {
+        *self = snapshot.0;
+    }
+
+    /// Produce an encoded raw document.
+    pub fn to_vec(&self) -> Result<Vec<u8>, bson::ser::Error> {
+        self.to_vec_with_options(&SerializeOptions::default())
+    }
+
+    /// Produce an encoded raw document, failing if it exceeds the limits in options.
+    pub fn to_vec_with_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<Vec<u8>, bson::ser::Error> {
+        check_serialize_nesting(|d| self.validate_nesting(d), options)?;
+        encode_to_vec(self.raw_len(), options, |buf| self.put(buf))
+    }
+
+    pub(super) fn try_into_document(self) -> Result<Document, bson::raw::Error> {
+        self.fields
+            .into_iter()
+            .map(|(k, v)| Ok((k.into_owned(), v.try_into()?)))
+            .collect()
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.fields.capacity()
+    }
+
+    pub(super) fn fields_iter(&self) -> map::Iter<'_, Cow<'a, str>, MutableValue<'a>> {
+        self.fields.iter()
     }
 
     pub(super) fn raw_len(&self) -> usize {
-        self.0
+        *self.cached_len.get_or_init(|| self.compute_raw_len())
+    }
+
+    /// Returns the encoded length of the document if it has been computed since the last mutation.
+    pub(super) fn cached_raw_len(&self) -> Option<usize> {
+        self.cached_len.get().copied()
+    }
+
+    fn compute_raw_len(&self) -> usize {
+        self.fields
             .iter()
             // 1 byte for type, key, 1 byte null terminator, value.
             .map(|(k, v)| 1 + raw_cstr_len(k.as_ref()) + v.raw_len())
@@ -77,7 +403,7 @@ impl<'a> ParsedDocument<'a> {
                 .try_into()
                 .expect("message len checked before put"),
         );
-        for (k, v) in self.0.iter() {
+        for (k, v) in self.fields.iter() {
             buf.put_u8(v.element_type() as u8);
             put_raw_cstr(k.as_ref(), buf)?;
             v.put(buf)?;
@@ -85,11 +411,128 @@ impl<'a> ParsedDocument<'a> {
         buf.put_u8(0);
         Ok(())
     }
+}
+
+/// The state of a [`ParsedDocument`] captured by [`ParsedDocument::snapshot()`].
+#[derive(Clone, Debug)]
+pub struct Snapshot<'a>(ParsedDocument<'a>);
+
+/// Errors that may occur when renaming a key with [`ParsedDocument::rename_key()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenameError {
+    /// The key to rename does not exist.
+    NotFound(String),
+    /// The new key already exists.
+    AlreadyExists(String),
+}
+
+impl std::fmt::Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(k) => write!(f, "key {} not found", k),
+            Self::AlreadyExists(k) => write!(f, "key {} already exists", k),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// A view into a single entry in a [`ParsedDocument`], which may be either vacant or occupied.
+///
+/// Constructed using [`ParsedDocument::entry`].
+pub enum Entry<'d, 'a> {
+    Occupied(OccupiedEntry<'d, 'a>),
+    Vacant(VacantEntry<'d, 'a>),
+}
+
+impl<'d, 'a> Entry<'d, 'a> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Occupied(e) => e.key(),
+            Self::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Insert value if the entry is vacant and return a mutable reference to the value.
+    pub fn or_insert<V: Into<MutableValue<'static>>>(self, value: V) -> &'d mut MutableValue<'a> {
+        match self {
+            Self::Occupied(e) => e.into_mut(),
+            Self::Vacant(e) => e.insert(value),
+        }
+    }
+
+    /// Insert the output of f if the entry is vacant and return a mutable reference to the value.
+    pub fn or_insert_with<F: FnOnce() -> MutableValue<'static>>(
+        self,
+        f: F,
+    ) -> &'d mut MutableValue<'a> {
+        match self {
+            Self::Occupied(e) => e.into_mut(),
+            Self::Vacant(e) => e.insert(f()),
+        }
+    }
+
+    /// Call f on the value if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut MutableValue<'a>)>(mut self, f: F) -> Self {
+        if let Self::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+/// An occupied entry in a [`ParsedDocument`]. Part of the [`Entry`] enum.
+pub struct OccupiedEntry<'d, 'a>(map::OccupiedEntry<'d, Cow<'a, str>, MutableValue<'a>>);
+
+impl<'d, 'a> OccupiedEntry<'d, 'a> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &str {
+        self.0.key().as_ref()
+    }
+
+    /// Returns a reference to the value of the entry.
+    pub fn get(&self) -> &MutableValue<'a> {
+        self.0.get()
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    pub fn get_mut(&mut self) -> &mut MutableValue<'a> {
+        self.0.get_mut()
+    }
+
+    /// Convert into a mutable reference to the value with the lifetime of the document.
+    pub fn into_mut(self) -> &'d mut MutableValue<'a> {
+        self.0.into_mut()
+    }
+
+    /// Replace the value of the entry and return the old value.
+    pub fn insert<V: Into<MutableValue<'static>>>(&mut self, value: V) -> MutableValue<'a> {
+        self.0.insert(value.into())
+    }
+
+    /// Remove the entry from the document and return the value.
+    ///
+    /// Like [`ParsedDocument::remove`] this preserves the order of the remaining keys and runs in
+    /// _O(n)_ time.
+    pub fn remove(self) -> MutableValue<'a> {
+        self.0.shift_remove()
+    }
+}
+
+/// A vacant entry in a [`ParsedDocument`]. Part of the [`Entry`] enum.
+pub struct VacantEntry<'d, 'a>(map::VacantEntry<'d, Cow<'a, str>, MutableValue<'a>>);
+
+impl<'d, 'a> VacantEntry<'d, 'a> {
+    /// Returns the key that would be used when inserting a value.
+    pub fn key(&self) -> &str {
+        self.0.key().as_ref()
+    }
 
-    // TODO: keys()
-    // TODO: values()
-    // TODO: values_mut()
-    // TODO: hash/btree map style entry()
+    /// Insert value at the end of the document and return a mutable reference to it.
+    pub fn insert<V: Into<MutableValue<'static>>>(self, value: V) -> &'d mut MutableValue<'a> {
+        self.0.insert(value.into())
+    }
 }
 
 impl<'a> TryFrom<&'a RawDocument> for ParsedDocument<'a> {
@@ -101,13 +544,25 @@ impl<'a> TryFrom<&'a RawDocument> for ParsedDocument<'a> {
             let (k, v) = e?;
             fields.insert(k.into(), v.into());
         }
-        Ok(Self(fields))
+        Ok(Self::with_fields(fields))
+    }
+}
+
+/// Parse an encoded BSON document, borrowing strings and nested documents from bytes.
+///
+/// Fails with a raw BSON parsing error if the bytes are not a valid document. Only top-level
+/// elements are checked; nested documents and arrays are validated when they are parsed.
+impl<'a> TryFrom<&'a [u8]> for ParsedDocument<'a> {
+    type Error = bson::raw::Error;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_from(RawDocument::from_bytes(value)?)
     }
 }
 
 impl From<Document> for ParsedDocument<'_> {
     fn from(value: Document) -> Self {
-        Self(
+        Self::with_fields(
             value
                 .into_iter()
                 .map(|(k, v)| (k.into(), v.into()))
@@ -116,24 +571,101 @@ impl From<Document> for ParsedDocument<'_> {
     }
 }
 
+/// Copies any borrowed data into a [`bson::Document`].
+///
+/// May fail with a raw BSON parsing error if the document contains an encoded document or array.
+impl TryFrom<ParsedDocument<'_>> for Document {
+    type Error = bson::raw::Error;
+
+    fn try_from(value: ParsedDocument<'_>) -> Result<Self, Self::Error> {
+        value.try_into_document()
+    }
+}
+
+impl TryFrom<&ParsedDocument<'_>> for RawDocumentBuf {
+    type Error = bson::ser::Error;
+
+    fn try_from(value: &ParsedDocument<'_>) -> Result<Self, Self::Error> {
+        // to_vec() always produces a correctly framed document.
+        Ok(RawDocumentBuf::from_bytes(value.to_vec()?).expect("well formed document"))
+    }
+}
+
+impl<'a> IntoIterator for ParsedDocument<'a> {
+    type Item = (Cow<'a, str>, MutableValue<'a>);
+    type IntoIter = ParsedDocumentIntoIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ParsedDocumentIntoIter(self.fields.into_iter())
+    }
+}
+
+/// An owning iterator over the entries of a [`ParsedDocument`] in order.
+pub struct ParsedDocumentIntoIter<'a>(map::IntoIter<Cow<'a, str>, MutableValue<'a>>);
+
+impl<'a> Iterator for ParsedDocumentIntoIter<'a> {
+    type Item = (Cow<'a, str>, MutableValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Documents are equal if they contain the same keys in the same order with equal values.
+impl PartialEq for ParsedDocument<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
 impl<'a, S: AsRef<str>> Index<S> for ParsedDocument<'a> {
     type Output = MutableValue<'a>;
 
     fn index(&self, index: S) -> &Self::Output {
-        &self.0[index.as_ref()]
+        &self.fields[index.as_ref()]
+    }
+}
+
+/// Extends a document with entries converted from keys and values.
+impl<'a, K: Into<String>, V: Into<MutableValue<'a>>> Extend<(K, V)> for ParsedDocument<'a> {
+    /// Insert all entries from iter, overwriting the values of existing keys in place.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.fields_mut().extend(
+            iter.into_iter()
+                .map(|(k, v)| (Cow::from(k.into()), v.into())),
+        )
+    }
+}
+
+/// Builds a document from entries converted from keys and values.
+impl<'a, K: Into<String>, V: Into<MutableValue<'a>>> FromIterator<(K, V)> for ParsedDocument<'a> {
+    /// Collect entries into a document. If a key appears more than once the last value is kept
+    /// at the position of the first occurrence.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::with_fields(
+            iter.into_iter()
+                .map(|(k, v)| (Cow::from(k.into()), v.into()))
+                .collect(),
+        )
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::borrow::Cow;
+
     use bson::{
         Binary, Bson, DateTime, Decimal128, Document, JavaScriptCodeWithScope, RawDocumentBuf,
-        Regex, Timestamp, bson, doc, oid::ObjectId, rawdoc, to_raw_document_buf,
+        Regex, Timestamp, bson, doc, oid::ObjectId, rawdoc, spec::ElementType, to_raw_document_buf,
     };
 
-    use crate::MutableValue;
+    use crate::{MutableDocument, MutableValue};
 
-    use super::ParsedDocument;
+    use super::{Entry, ParsedDocument, RenameError};
 
     fn doc_to_vec(doc: &ParsedDocument<'_>) -> Vec<u8> {
         let mut out = vec![];
@@ -141,6 +673,23 @@ mod test {
         out
     }
 
+    /// Returns a DbPointer value, which can only be constructed by decoding it.
+    fn db_pointer() -> Bson {
+        let mut bytes = vec![0u8; 4];
+        bytes.push(ElementType::DbPointer as u8);
+        bytes.extend_from_slice(b"l\0");
+        bytes.extend_from_slice(&8i32.to_le_bytes());
+        bytes.extend_from_slice(b"db.coll\0");
+        bytes.extend_from_slice(&[0xbd; 12]);
+        bytes.push(0);
+        let len = bytes.len() as i32;
+        bytes[..4].copy_from_slice(&len.to_le_bytes());
+        Document::try_from(RawDocumentBuf::from_bytes(bytes).unwrap())
+            .unwrap()
+            .remove("l")
+            .unwrap()
+    }
+
     fn doc_all_types_owned() -> Document {
         let mut doc = Document::new();
         doc.insert("a", 1.0f64);
@@ -166,6 +715,7 @@ mod test {
                 options: "i".into(),
             },
         );
+        doc.insert("l", db_pointer());
         doc.insert("m", Bson::JavaScriptCode("some code".into()));
         doc.insert("n", Bson::Symbol("symbol".into()));
         doc.insert(
@@ -231,6 +781,35 @@ mod test {
         assert!(doc.get("z").is_none());
     }
 
+    #[test]
+    fn typed_getters() {
+        let mut doc = ParsedDocument::from(doc_all_types_owned());
+        assert_eq!(doc.get_f64("a"), Some(1.0));
+        assert_eq!(doc.get_str("b"), Some("str"));
+        assert!(doc.get_document("c").is_some());
+        assert!(doc.get_array("d").is_some());
+        assert_eq!(doc.get_binary_generic("e"), Some([1u8, 2, 3].as_ref()));
+        assert_eq!(
+            doc.get_object_id("g"),
+            Some(ObjectId::from_bytes([0xae; 12]))
+        );
+        assert_eq!(doc.get_bool("h"), Some(true));
+        assert_eq!(
+            doc.get_datetime("i"),
+            Some(DateTime::from_millis(1234567890))
+        );
+        assert_eq!(doc.get_i32("p"), Some(7));
+        assert!(doc.get_document_mut("c").is_some());
+        assert!(doc.get_array_mut("d").is_some());
+
+        // Wrong type or missing.
+        assert_eq!(doc.get_i64("p"), None);
+        assert_eq!(doc.get_str("a"), None);
+        assert!(doc.get_document("d").is_none());
+        assert!(doc.get_array_mut("c").is_none());
+        assert_eq!(doc.get_bool("z"), None);
+    }
+
     #[test]
     fn index() {
         let doc = ParsedDocument::from(doc_all_types_owned());
@@ -245,6 +824,53 @@ mod test {
         let _ = doc["z"];
     }
 
+    #[test]
+    fn keys_and_values() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("foo", 5);
+        doc.insert("bar", 7);
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["foo", "bar"]);
+        assert_eq!(
+            doc.values()
+                .filter_map(MutableValue::as_i32)
+                .collect::<Vec<_>>(),
+            vec![5, 7]
+        );
+
+        for v in doc.values_mut() {
+            *v = (v.as_i32().unwrap() * 2).into();
+        }
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "foo": 10, "bar": 14 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn eq() {
+        let raw_doc = doc_all_types_unowned();
+        let doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(doc, ParsedDocument::from(doc_all_types_owned()));
+
+        let mut a = ParsedDocument::new();
+        a.insert("foo", 5);
+        a.insert("bar", "bat");
+        let mut b = ParsedDocument::new();
+        b.insert("bar", "bat");
+        b.insert("foo", 5);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn into_iter() {
+        let raw_doc = rawdoc! { "foo": 5, "bar": "bat" };
+        let doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        let mut it = doc.into_iter();
+        assert_eq!(it.next(), Some(("foo".into(), MutableValue::from(5))));
+        assert_eq!(it.next(), Some(("bar".into(), MutableValue::from("bat"))));
+        assert_eq!(it.next(), None);
+    }
+
     #[test]
     fn insert_and_replace() {
         let mut doc = ParsedDocument::new();
@@ -262,6 +888,52 @@ mod test {
         );
     }
 
+    #[test]
+    fn try_from_bytes() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "d" } };
+        let doc = ParsedDocument::try_from(raw.as_bytes()).unwrap();
+        assert_eq!(doc_to_vec(&doc), raw.as_bytes());
+
+        assert!(ParsedDocument::try_from(&raw.as_bytes()[1..]).is_err());
+        let bytes = raw.as_bytes();
+        assert!(ParsedDocument::try_from(&bytes[..bytes.len() - 2]).is_err());
+        let mut retyped = bytes.to_vec();
+        retyped[4] = 0x7f;
+        assert!(ParsedDocument::try_from(retyped.as_slice()).is_err());
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut doc = ParsedDocument::with_capacity(8);
+        assert!(doc.is_empty());
+        assert!(doc.capacity() >= 8);
+        doc.insert("a", 1);
+        assert_eq!(doc_to_vec(&doc), rawdoc! { "a": 1 }.as_bytes());
+    }
+
+    #[test]
+    fn insert_borrowed() {
+        let raw = rawdoc! { "a": { "b": 1 }, "c": "d" };
+        let mut doc = ParsedDocument::new();
+        for e in raw.iter() {
+            let (k, v) = e.unwrap();
+            assert!(doc.insert_borrowed(k, v).is_none());
+        }
+        assert!(matches!(
+            doc.get("a"),
+            Some(MutableValue::Document(MutableDocument::Borrowed(_)))
+        ));
+        assert!(matches!(
+            doc.get("c"),
+            Some(MutableValue::String(Cow::Borrowed("d")))
+        ));
+        assert!(doc.insert_borrowed("c", 2).is_some());
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "a": { "b": 1 }, "c": 2 }.as_bytes()
+        );
+    }
+
     #[test]
     fn remove() {
         let mut doc = ParsedDocument::new();
@@ -278,6 +950,167 @@ mod test {
         assert!(doc.remove("foo").is_none());
     }
 
+    #[test]
+    fn field_types() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("foo", 5);
+        doc.insert("bar", "bat");
+        assert_eq!(
+            doc.field_types().collect::<Vec<_>>(),
+            vec![("foo", ElementType::Int32), ("bar", ElementType::String)]
+        );
+    }
+
+    #[test]
+    fn indexmap() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("b", 2);
+        doc.insert("a", 1);
+        let mut map = doc.into_indexmap();
+        map.sort_keys();
+        let doc = ParsedDocument::from_indexmap(map);
+        assert_eq!(doc_to_vec(&doc), rawdoc! { "a": 1, "b": 2 }.as_bytes());
+    }
+
+    #[test]
+    fn as_indexmap() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        assert_eq!(doc.as_indexmap().get_index_of("a"), Some(0));
+        assert_eq!(doc.raw_len(), 12);
+        doc.as_indexmap_mut()
+            .insert(Cow::Borrowed("b"), MutableValue::from("c"));
+        assert_eq!(doc.raw_len(), 21);
+        assert_eq!(doc_to_vec(&doc), rawdoc! { "a": 1, "b": "c" }.as_bytes());
+    }
+
+    #[test]
+    fn remove_entry() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("foo", 5);
+        doc.insert("bar", "bat");
+        let (key, value) = doc.remove_entry("foo").unwrap();
+        assert_eq!(key, "foo");
+        assert_eq!(value.as_i32(), Some(5));
+        assert_eq!(doc_to_vec(&doc), rawdoc! { "bar": "bat" }.as_bytes());
+        assert!(doc.remove_entry("foo").is_none());
+    }
+
+    #[test]
+    fn retain() {
+        let mut doc = ParsedDocument::from(doc_all_types_owned());
+        doc.retain(|k, v| {
+            if k == "h" {
+                *v = false.into();
+            }
+            k == "b" || k == "h" || k == "p"
+        });
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "b": "str", "h": false, "p": 7 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn extend() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        doc.insert("b", 2);
+        assert_eq!(doc.raw_len(), 19);
+        doc.extend([
+            ("b".to_string(), MutableValue::from("x")),
+            ("c".to_string(), MutableValue::from(3)),
+        ]);
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(doc["b"].as_str(), Some("x"));
+        assert_eq!(doc.raw_len(), doc.to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn from_iter() {
+        let doc: ParsedDocument = [("a", 1), ("b", 2), ("a", 3)].into_iter().collect();
+        assert_eq!(doc.to_vec().unwrap(), rawdoc! { "a": 3, "b": 2 }.as_bytes());
+
+        let mut doc: ParsedDocument = vec![("x".to_string(), MutableValue::from("y"))]
+            .into_iter()
+            .collect();
+        doc.extend([("z", true)]);
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "x": "y", "z": true }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn map_values() {
+        let widen = |v: MutableValue<'static>| match v {
+            MutableValue::Int32(i) => MutableValue::Int64(i.into()),
+            v => v,
+        };
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        doc.insert("b", "x");
+        assert_eq!(doc.raw_len(), 21);
+        doc.map_values(widen);
+        assert_eq!(doc.raw_len(), 25);
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": 1i64, "b": "x" }.as_bytes()
+        );
+
+        let doc = doc.map_values_into_iter(|v| match v {
+            MutableValue::String(_) => MutableValue::from("redacted"),
+            v => v,
+        });
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": 1i64, "b": "redacted" }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn snapshot_rollback() {
+        let raw_doc = rawdoc! { "a": 1, "b": { "c": 2 } };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        let snapshot = doc.snapshot();
+        doc.insert("a", "x");
+        doc.remove("b");
+        doc.insert("d", true);
+        doc.rollback(snapshot);
+        assert_eq!(doc.to_vec().unwrap(), raw_doc.as_bytes());
+    }
+
+    #[test]
+    fn rename_key() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        doc.insert("b", 2);
+        doc.insert("c", 3);
+        assert_eq!(doc.raw_len(), 26);
+        doc.rename_key("b", "bb").unwrap();
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["a", "bb", "c"]);
+        assert_eq!(doc["bb"].as_i32(), Some(2));
+        assert_eq!(doc.raw_len(), 27);
+        doc.rename_key("a", "a").unwrap();
+        assert_eq!(
+            doc.rename_key("z", "y"),
+            Err(RenameError::NotFound("z".to_string()))
+        );
+        assert_eq!(
+            doc.rename_key("a", "c"),
+            Err(RenameError::AlreadyExists("c".to_string()))
+        );
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["a", "bb", "c"]);
+    }
+
+    #[test]
+    fn into_bson_types() {
+        let raw_doc = to_raw_document_buf(&doc_all_types_owned()).unwrap();
+        let doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(RawDocumentBuf::try_from(&doc).unwrap(), raw_doc);
+        assert_eq!(Document::try_from(doc).unwrap(), doc_all_types_owned());
+    }
+
     #[test]
     fn get_mut() {
         let mut doc = ParsedDocument::new();
@@ -295,6 +1128,148 @@ mod test {
         assert_eq!(doc_to_vec(&doc), rawdoc! { "foo": "bar" }.as_bytes());
     }
 
+    #[test]
+    fn get_or_insert() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        assert_eq!(doc.get_or_insert("a", 2).as_i32(), Some(1));
+        assert_eq!(doc.get_or_insert("b", 2).as_i32(), Some(2));
+        *doc.get_or_insert_with("c", || MutableValue::from(3)) = MutableValue::from("x");
+        assert_eq!(
+            doc.get_or_insert_with("c", || unreachable!()).as_str(),
+            Some("x")
+        );
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": 1, "b": 2, "c": "x" }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn get_index() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        doc.insert("b", 2);
+        assert_eq!(doc.position_of("b"), Some(1));
+        assert_eq!(doc.position_of("c"), None);
+        let (key, value) = doc.get_index(1).unwrap();
+        assert_eq!((key, value.as_i32()), ("b", Some(2)));
+        assert!(doc.get_index(2).is_none());
+
+        assert_eq!(doc.raw_len(), 19);
+        let (key, value) = doc.get_index_mut(0).unwrap();
+        assert_eq!(key, "a");
+        *value = MutableValue::from("xyz");
+        assert_eq!(doc.raw_len(), 23);
+        assert!(doc.get_index_mut(2).is_none());
+    }
+
+    #[test]
+    fn reorder() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", 1);
+        doc.insert("b", 2);
+        doc.insert("c", 3);
+        doc.swap_indices(0, 2);
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["c", "b", "a"]);
+        doc.move_index(0, 2);
+        assert_eq!(doc.keys().collect::<Vec<_>>(), vec!["b", "a", "c"]);
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "b": 2, "a": 1, "c": 3 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn into_owned_outlives_source() {
+        let raw_doc = to_raw_document_buf(&doc_all_types_owned()).unwrap();
+        let doc = ParsedDocument::try_from(raw_doc.as_ref())
+            .unwrap()
+            .into_owned()
+            .unwrap();
+        drop(raw_doc);
+        assert_eq!(doc.try_into_document().unwrap(), doc_all_types_owned());
+    }
+
+    #[test]
+    fn entry() {
+        let mut doc = ParsedDocument::new();
+        assert_eq!(doc.entry("count").or_insert(0).as_i32(), Some(0));
+        doc.entry("count")
+            .and_modify(|v| *v = (v.as_i32().unwrap() + 1).into())
+            .or_insert(0);
+        doc.entry("tags")
+            .or_insert_with(|| MutableValue::Array(Vec::<MutableValue>::new().into()))
+            .as_array_mut()
+            .unwrap()
+            .to_parsed()
+            .unwrap()
+            .push("a".into());
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "count": 1, "tags": ["a"] }.as_bytes()
+        );
+
+        match doc.entry("count") {
+            Entry::Occupied(e) => {
+                assert_eq!(e.key(), "count");
+                assert_eq!(e.remove().as_i32(), Some(1));
+            }
+            Entry::Vacant(_) => panic!("count is present"),
+        }
+        match doc.entry("count") {
+            Entry::Occupied(_) => panic!("count was removed"),
+            Entry::Vacant(e) => {
+                assert_eq!(e.key(), "count");
+                e.insert(2);
+            }
+        }
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "tags": ["a"], "count": 2 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn cached_len_invalidated() {
+        let raw_doc = rawdoc! { "foo": 5, "bar": { "id": 0 } };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(doc.raw_len(), raw_doc.as_bytes().len());
+
+        doc.insert("quux", 7);
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "foo": 5, "bar": { "id": 0 }, "quux": 7 }.as_bytes()
+        );
+
+        let emb_doc = doc
+            .get_mut("bar")
+            .and_then(MutableValue::as_doc_mut)
+            .unwrap()
+            .to_parsed()
+            .unwrap();
+        assert_eq!(emb_doc.raw_len(), 13);
+        emb_doc.insert("name", "foo");
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "foo": 5, "bar": { "id": 0, "name": "foo" }, "quux": 7 }.as_bytes()
+        );
+
+        for v in doc.values_mut() {
+            *v = MutableValue::Null;
+        }
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "foo": null, "bar": null, "quux": null }.as_bytes()
+        );
+
+        doc.remove("bar");
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "foo": null, "quux": null }.as_bytes()
+        );
+    }
+
     #[test]
     fn clear() {
         let mut doc = ParsedDocument::from(doc_all_types_owned());
@@ -0,0 +1,310 @@
+use bson::{Array, Bson, Document};
+
+use crate::{
+    MutableValue, ParsedDocument, PointerError,
+    pointer::{Parent, parent_mut, parse_pointer, pointer_index},
+};
+
+/// A single JSON Patch (RFC 6902) operation. Paths are JSON Pointers (RFC 6901).
+#[derive(Clone, Debug)]
+pub enum PatchOperation {
+    /// Insert value at path, replacing any existing document field or shifting array elements.
+    Add {
+        path: String,
+        value: MutableValue<'static>,
+    },
+    /// Remove the value at path, which must exist.
+    Remove { path: String },
+    /// Replace the value at path, which must exist.
+    Replace {
+        path: String,
+        value: MutableValue<'static>,
+    },
+    /// Remove the value at from and add it at path.
+    Move { from: String, path: String },
+    /// Add a copy of the value at from at path.
+    Copy { from: String, path: String },
+    /// Check that the value at path is equal to value.
+    Test {
+        path: String,
+        value: MutableValue<'static>,
+    },
+}
+
+impl PatchOperation {
+    /// Parse a patch from an array of operation documents like
+    /// `[{"op": "add", "path": "/a", "value": 1}]`.
+    pub fn from_array(patch: &Array) -> Result<Vec<PatchOperation>, PatchError> {
+        patch
+            .iter()
+            .map(|op| match op {
+                Bson::Document(d) => PatchOperation::try_from(d),
+                _ => Err(PatchError::InvalidOperation(format!(
+                    "operation {} is not a document",
+                    op
+                ))),
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<&Document> for PatchOperation {
+    type Error = PatchError;
+
+    fn try_from(op: &Document) -> Result<Self, Self::Error> {
+        let field = |name: &str| {
+            op.get_str(name).map(str::to_string).map_err(|_| {
+                PatchError::InvalidOperation(format!("operation {} requires string {}", op, name))
+            })
+        };
+        let value = || {
+            op.get("value")
+                .cloned()
+                .map(MutableValue::from)
+                .ok_or_else(|| {
+                    PatchError::InvalidOperation(format!("operation {} requires value", op))
+                })
+        };
+        Ok(match field("op")?.as_str() {
+            "add" => Self::Add {
+                path: field("path")?,
+                value: value()?,
+            },
+            "remove" => Self::Remove {
+                path: field("path")?,
+            },
+            "replace" => Self::Replace {
+                path: field("path")?,
+                value: value()?,
+            },
+            "move" => Self::Move {
+                from: field("from")?,
+                path: field("path")?,
+            },
+            "copy" => Self::Copy {
+                from: field("from")?,
+                path: field("path")?,
+            },
+            "test" => Self::Test {
+                path: field("path")?,
+                value: value()?,
+            },
+            other => {
+                return Err(PatchError::InvalidOperation(format!(
+                    "unknown operation {}",
+                    other
+                )));
+            }
+        })
+    }
+}
+
+/// Errors that may occur when applying a JSON Patch with [`ParsedDocument::apply_patch`].
+#[derive(Clone, Debug)]
+pub enum PatchError {
+    /// The patch contains an operation that is malformed or cannot be applied.
+    InvalidOperation(String),
+    /// A path could not be resolved.
+    Pointer(PointerError),
+    /// A path that must exist does not refer to a value.
+    NotFound(String),
+    /// The value at path was not equal to the value in a `test` operation.
+    TestFailed(String),
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidOperation(m) => write!(f, "{}", m),
+            Self::Pointer(e) => write!(f, "{}", e),
+            Self::NotFound(p) => write!(f, "no value at {}", p),
+            Self::TestFailed(p) => write!(f, "test failed at {}", p),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl From<PointerError> for PatchError {
+    fn from(value: PointerError) -> Self {
+        Self::Pointer(value)
+    }
+}
+
+impl From<bson::raw::Error> for PatchError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Pointer(value.into())
+    }
+}
+
+impl<'a> ParsedDocument<'a> {
+    /// Apply a sequence of JSON Patch (RFC 6902) operations.
+    ///
+    /// Operations are applied in order. If any operation fails the document is restored to its
+    /// state before the patch was applied.
+    pub fn apply_patch(&mut self, patch: &[PatchOperation]) -> Result<(), PatchError> {
+        let snapshot = self.snapshot();
+        for op in patch {
+            if let Err(e) = self.apply_patch_operation(op) {
+                self.rollback(snapshot);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_patch_operation(&mut self, op: &PatchOperation) -> Result<(), PatchError> {
+        match op {
+            PatchOperation::Add { path, value } => self.patch_add(path, value.clone()),
+            PatchOperation::Remove { path } => self
+                .remove_pointer(path)?
+                .map(|_| ())
+                .ok_or_else(|| PatchError::NotFound(path.clone())),
+            PatchOperation::Replace { path, value } => {
+                if self.get_pointer(path)?.is_none() {
+                    return Err(PatchError::NotFound(path.clone()));
+                }
+                self.set_pointer(path, value.clone())?;
+                Ok(())
+            }
+            PatchOperation::Move { from, path } => {
+                if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                    return Err(PatchError::InvalidOperation(format!(
+                        "cannot move {} into its child {}",
+                        from, path
+                    )));
+                }
+                let value = self
+                    .remove_pointer(from)?
+                    .ok_or_else(|| PatchError::NotFound(from.clone()))?;
+                self.patch_add(path, value)
+            }
+            PatchOperation::Copy { from, path } => {
+                let value = self
+                    .get_pointer(from)?
+                    .ok_or_else(|| PatchError::NotFound(from.clone()))?
+                    .into_owned();
+                self.patch_add(path, value)
+            }
+            PatchOperation::Test { path, value } => match self.get_pointer(path)? {
+                Some(v) if v.as_ref() == value => Ok(()),
+                _ => Err(PatchError::TestFailed(path.clone())),
+            },
+        }
+    }
+
+    fn patch_add(&mut self, path: &str, value: MutableValue<'a>) -> Result<(), PatchError> {
+        let tokens = parse_pointer(path)?;
+        let (last, parents) = tokens.split_last().ok_or(PointerError::Root)?;
+        match parent_mut(self, path, parents)? {
+            Parent::Document(d) => {
+                d.insert_borrowed(last.to_string(), value);
+            }
+            Parent::Array(a) => {
+                let index = match last.as_ref() {
+                    "-" => a.len(),
+                    token => pointer_index(token)
+                        .filter(|i| *i <= a.len())
+                        .ok_or_else(|| PointerError::InvalidArrayIndex(path.to_string()))?,
+                };
+                a.insert(index, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{bson, rawdoc};
+
+    use crate::{MutableValue, ParsedDocument, PointerError};
+
+    use super::{PatchError, PatchOperation};
+
+    fn patch(ops: bson::Bson) -> Vec<PatchOperation> {
+        PatchOperation::from_array(ops.as_array().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn apply_patch() {
+        let raw = rawdoc! { "a": { "b": [1, 2] }, "c": 3, "d": "x" };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        doc.apply_patch(&patch(bson!([
+            { "op": "test", "path": "/c", "value": 3 },
+            { "op": "add", "path": "/a/b/1", "value": 4 },
+            { "op": "add", "path": "/a/b/-", "value": 5 },
+            { "op": "add", "path": "/e", "value": { "f": true } },
+            { "op": "remove", "path": "/c" },
+            { "op": "replace", "path": "/a/b/0", "value": 6 },
+            { "op": "move", "from": "/d", "path": "/e/g" },
+            { "op": "copy", "from": "/e/f", "path": "/h" },
+        ])))
+        .unwrap();
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! {
+                "a": { "b": [6, 4, 2, 5] },
+                "e": { "f": true, "g": "x" },
+                "h": true,
+            }
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn apply_patch_atomic() {
+        let raw = rawdoc! { "a": 1, "b": [1] };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        let mut apply = |ops| {
+            let err = doc.apply_patch(&patch(ops)).unwrap_err();
+            assert_eq!(doc.to_vec().unwrap(), raw.as_bytes());
+            err
+        };
+        assert!(matches!(
+            apply(bson!([
+                { "op": "add", "path": "/c", "value": 1 },
+                { "op": "test", "path": "/a", "value": 2 },
+            ])),
+            PatchError::TestFailed(p) if p == "/a"
+        ));
+        assert!(matches!(
+            apply(bson!([
+                { "op": "remove", "path": "/a" },
+                { "op": "remove", "path": "/z" },
+            ])),
+            PatchError::NotFound(p) if p == "/z"
+        ));
+        assert!(matches!(
+            apply(bson!([{ "op": "replace", "path": "/b/1", "value": 1 }])),
+            PatchError::NotFound(_)
+        ));
+        assert!(matches!(
+            apply(bson!([{ "op": "add", "path": "/b/2", "value": 1 }])),
+            PatchError::Pointer(PointerError::InvalidArrayIndex(_))
+        ));
+        assert!(matches!(
+            apply(bson!([{ "op": "move", "from": "/b", "path": "/b/0" }])),
+            PatchError::InvalidOperation(_)
+        ));
+    }
+
+    #[test]
+    fn from_array_invalid() {
+        for ops in [
+            bson!([1]),
+            bson!([{ "op": "frob", "path": "/a" }]),
+            bson!([{ "op": "add", "path": "/a" }]),
+            bson!([{ "op": "move", "path": "/a" }]),
+        ] {
+            assert!(matches!(
+                PatchOperation::from_array(ops.as_array().unwrap()),
+                Err(PatchError::InvalidOperation(_))
+            ));
+        }
+        assert!(matches!(
+            &patch(bson!([{ "op": "test", "path": "/a", "value": "x" }]))[0],
+            PatchOperation::Test { path, value: MutableValue::String(s) } if path == "/a" && s == "x"
+        ));
+    }
+}
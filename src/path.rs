@@ -0,0 +1,365 @@
+use std::borrow::Cow;
+
+use crate::{Entry, MutableArray, MutableDocument, MutableValue, ParsedDocument};
+
+/// Errors that may occur when mutating a [`ParsedDocument`] using a dot-notation path.
+#[derive(Clone, Debug)]
+pub enum PathError {
+    /// An intermediate document or array could not be parsed.
+    Raw(bson::raw::Error),
+    /// The path prefix refers to a value that is not a document or array.
+    NotTraversable(String),
+    /// The path prefix refers to an array and the next segment is not an array index.
+    InvalidArrayIndex(String),
+}
+
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw(e) => write!(f, "{}", e),
+            Self::NotTraversable(p) => write!(f, "value at {} is not a document or array", p),
+            Self::InvalidArrayIndex(p) => write!(f, "{} is not a valid array index", p),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl From<bson::raw::Error> for PathError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+/// Returns the path prefix ending at segment i.
+fn path_prefix(segments: &[&str], i: usize) -> String {
+    segments[..=i].join(".")
+}
+
+impl<'a> ParsedDocument<'a> {
+    /// Get the value at a dot-notation path like `"a.b.0.c"`, where numeric segments are used to
+    /// index into arrays.
+    ///
+    /// Values are returned by reference if they have been parsed, or decoded without allocating
+    /// when the path descends into a [`bson::RawDocument`] or [`bson::RawArray`]. Malformed raw
+    /// values are treated as missing.
+    pub fn get_path(&self, path: &str) -> Option<Cow<'_, MutableValue<'a>>> {
+        let mut segments = path.split('.');
+        let mut value = Cow::Borrowed(self.get(segments.next()?)?);
+        for segment in segments {
+            value = child(value, segment)?;
+        }
+        Some(value)
+    }
+
+    /// Get the value at the path given by keys, like [`Self::get_path`] but without splitting on
+    /// `.` so that keys may themselves contain dots. Numeric keys index into arrays.
+    pub fn get_nested(&self, keys: &[&str]) -> Option<Cow<'_, MutableValue<'a>>> {
+        let (first, rest) = keys.split_first()?;
+        let mut value = Cow::Borrowed(self.get(first)?);
+        for key in rest {
+            value = child(value, key)?;
+        }
+        Some(value)
+    }
+
+    /// Get a mutable reference to the value at the path given by keys, parsing any raw documents
+    /// or arrays along the path. Numeric keys index into arrays.
+    ///
+    /// Returns `Ok(None)` if the path does not exist. May fail with a raw BSON parsing error.
+    pub fn get_nested_mut(
+        &mut self,
+        keys: &[&str],
+    ) -> Result<Option<&mut MutableValue<'a>>, bson::raw::Error> {
+        let Some((first, rest)) = keys.split_first() else {
+            return Ok(None);
+        };
+        let mut value = match self.get_mut(first) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        for key in rest {
+            let next = match value {
+                MutableValue::Document(d) => d.to_parsed()?.get_mut(key),
+                MutableValue::Array(a) => {
+                    let values = a.to_parsed()?;
+                    array_index(key).and_then(|i| values.get_mut(i))
+                }
+                _ => None,
+            };
+            value = match next {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+        }
+        Ok(Some(value))
+    }
+
+    /// Set the value at a dot-notation path like `"a.b.0.c"` and return the previous value if any.
+    ///
+    /// Any raw documents or arrays along the path are parsed and missing intermediate documents
+    /// are created. Setting an array index past the end of the array pads it with `Null` values.
+    /// Fails if the path traverses a value that is not a document or array.
+    pub fn set_path<V: Into<MutableValue<'static>>>(
+        &mut self,
+        path: &str,
+        value: V,
+    ) -> Result<Option<MutableValue<'a>>, PathError> {
+        let segments = path.split('.').collect::<Vec<_>>();
+        let (last, parents) = segments
+            .split_last()
+            .expect("split yields at least one item");
+        if parents.is_empty() {
+            return Ok(set_in_doc(self, last, value.into()));
+        }
+
+        let mut parent = self
+            .entry(parents[0])
+            .or_insert_with(|| ParsedDocument::new().into());
+        for (i, segment) in parents.iter().enumerate().skip(1) {
+            parent = match parent {
+                MutableValue::Document(d) => d
+                    .to_parsed()?
+                    .entry(*segment)
+                    .or_insert_with(|| ParsedDocument::new().into()),
+                MutableValue::Array(a) => {
+                    let values = a.to_parsed()?;
+                    let index = array_index(segment)
+                        .ok_or_else(|| PathError::InvalidArrayIndex(path_prefix(&segments, i)))?;
+                    if index >= values.len() {
+                        values.resize(index, MutableValue::Null);
+                        values.push(ParsedDocument::new().into());
+                    }
+                    &mut values[index]
+                }
+                _ => return Err(PathError::NotTraversable(path_prefix(&segments, i - 1))),
+            };
+        }
+
+        match parent {
+            MutableValue::Document(d) => Ok(set_in_doc(d.to_parsed()?, last, value.into())),
+            MutableValue::Array(a) => {
+                let values = a.to_parsed()?;
+                let index = array_index(last).ok_or_else(|| {
+                    PathError::InvalidArrayIndex(path_prefix(&segments, parents.len()))
+                })?;
+                if index < values.len() {
+                    Ok(Some(std::mem::replace(&mut values[index], value.into())))
+                } else {
+                    values.resize(index, MutableValue::Null);
+                    values.push(value.into());
+                    Ok(None)
+                }
+            }
+            _ => Err(PathError::NotTraversable(path_prefix(
+                &segments,
+                parents.len() - 1,
+            ))),
+        }
+    }
+
+    /// Remove the value at a dot-notation path like `"a.b.0.c"` and return it if present.
+    ///
+    /// Any raw documents or arrays along the path are parsed. Removing an array element shifts
+    /// all subsequent elements. If the path does not exist this returns `Ok(None)`.
+    pub fn remove_path(&mut self, path: &str) -> Result<Option<MutableValue<'a>>, PathError> {
+        let segments = path.split('.').collect::<Vec<_>>();
+        let (last, parents) = segments
+            .split_last()
+            .expect("split yields at least one item");
+        if parents.is_empty() {
+            return Ok(self.remove(last));
+        }
+
+        let Some(parent) = self.get_nested_mut(parents)? else {
+            return Ok(None);
+        };
+        match parent {
+            MutableValue::Document(d) => Ok(d.to_parsed()?.remove(last)),
+            MutableValue::Array(a) => {
+                let values = a.to_parsed()?;
+                Ok(array_index(last)
+                    .filter(|i| *i < values.len())
+                    .map(|i| values.remove(i)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+fn array_index(segment: &str) -> Option<usize> {
+    segment.parse::<usize>().ok()
+}
+
+fn set_in_doc<'a>(
+    doc: &mut ParsedDocument<'a>,
+    key: &str,
+    value: MutableValue<'static>,
+) -> Option<MutableValue<'a>> {
+    match doc.entry(key) {
+        Entry::Occupied(mut e) => Some(e.insert(value)),
+        Entry::Vacant(e) => {
+            e.insert(value);
+            None
+        }
+    }
+}
+
+/// Returns the child of value named by segment, borrowing from value where possible.
+fn child<'v, 'a>(
+    value: Cow<'v, MutableValue<'a>>,
+    segment: &str,
+) -> Option<Cow<'v, MutableValue<'a>>> {
+    child_with_index(value, segment, array_index)
+}
+
+/// Like [`child`] but parses array index segments with array_index.
+pub(crate) fn child_with_index<'v, 'a>(
+    value: Cow<'v, MutableValue<'a>>,
+    segment: &str,
+    array_index: fn(&str) -> Option<usize>,
+) -> Option<Cow<'v, MutableValue<'a>>> {
+    match value {
+        Cow::Borrowed(MutableValue::Document(MutableDocument::Owned(d))) => {
+            d.get(segment).map(Cow::Borrowed)
+        }
+        Cow::Borrowed(MutableValue::Array(MutableArray::Owned(a))) => {
+            a.get(array_index(segment)?).map(Cow::Borrowed)
+        }
+        Cow::Owned(MutableValue::Document(MutableDocument::Owned(mut d))) => {
+            d.remove(segment).map(Cow::Owned)
+        }
+        Cow::Owned(MutableValue::Array(MutableArray::Owned(mut a))) => {
+            let index = array_index(segment)?;
+            (index < a.len()).then(|| Cow::Owned(a.swap_remove(index)))
+        }
+        value => match value.as_ref() {
+            MutableValue::Document(MutableDocument::Borrowed(d)) => {
+                d.get(segment).ok().flatten().map(|v| Cow::Owned(v.into()))
+            }
+            MutableValue::Array(MutableArray::Borrowed(a)) => a
+                .get(array_index(segment)?)
+                .ok()
+                .flatten()
+                .map(|v| Cow::Owned(v.into())),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableValue, ParsedDocument};
+
+    use super::PathError;
+
+    fn doc_to_vec(doc: &ParsedDocument<'_>) -> Vec<u8> {
+        let mut out = vec![];
+        doc.put(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn get_path() {
+        let raw_doc = rawdoc! { "a": { "b": [1, { "c": "d" }] }, "e": 2 };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(doc.get_path("e").unwrap().as_i32(), Some(2));
+        assert_eq!(doc.get_path("a.b.0").unwrap().as_i32(), Some(1));
+        assert_eq!(doc.get_path("a.b.1.c").unwrap().as_str(), Some("d"));
+        assert!(doc.get_path("a.b.2").is_none());
+        assert!(doc.get_path("a.b.x").is_none());
+        assert!(doc.get_path("e.f").is_none());
+        assert!(doc.get_path("z").is_none());
+
+        doc.get_mut("a")
+            .and_then(MutableValue::as_doc_mut)
+            .unwrap()
+            .to_parsed()
+            .unwrap();
+        assert_eq!(doc.get_path("a.b.1.c").unwrap().as_str(), Some("d"));
+    }
+
+    #[test]
+    fn get_nested() {
+        let raw_doc = rawdoc! { "a": { "b.c": [1, { "d": "e" }] }, "f": 2 };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(doc.get_nested(&["f"]).unwrap().as_i32(), Some(2));
+        assert_eq!(
+            doc.get_nested(&["a", "b.c", "1", "d"]).unwrap().as_str(),
+            Some("e")
+        );
+        assert!(doc.get_path("a.b.c").is_none());
+        assert!(doc.get_nested(&["a", "b"]).is_none());
+        assert!(doc.get_nested(&["f", "g"]).is_none());
+        assert!(doc.get_nested(&[]).is_none());
+
+        *doc.get_nested_mut(&["a", "b.c", "0"]).unwrap().unwrap() = MutableValue::from(3);
+        assert!(doc.get_nested_mut(&["a", "x"]).unwrap().is_none());
+        assert!(doc.get_nested_mut(&["f", "g"]).unwrap().is_none());
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "a": { "b.c": [3, { "d": "e" }] }, "f": 2 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn set_path() {
+        let raw_doc = rawdoc! { "a": { "b": [1, { "c": "d" }] }, "e": 2 };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(doc.set_path("e", 3).unwrap().unwrap().as_i32(), Some(2));
+        assert_eq!(
+            doc.set_path("a.b.1.c", "f").unwrap().unwrap().as_str(),
+            Some("d")
+        );
+        assert!(doc.set_path("a.b.3", true).unwrap().is_none());
+        assert!(doc.set_path("x.y.z", 1).unwrap().is_none());
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! {
+                "a": { "b": [1, { "c": "f" }, null, true] },
+                "e": 3,
+                "x": { "y": { "z": 1 } },
+            }
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn set_path_invalid() {
+        let raw_doc = rawdoc! { "a": { "b": [1] }, "e": 2 };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert!(matches!(
+            doc.set_path("e.f", 1),
+            Err(PathError::NotTraversable(p)) if p == "e"
+        ));
+        assert!(matches!(
+            doc.set_path("a.b.0.c", 1),
+            Err(PathError::NotTraversable(p)) if p == "a.b.0"
+        ));
+        assert!(matches!(
+            doc.set_path("a.b.c", 1),
+            Err(PathError::InvalidArrayIndex(p)) if p == "a.b.c"
+        ));
+    }
+
+    #[test]
+    fn remove_path() {
+        let raw_doc = rawdoc! { "a": { "b": [1, { "c": "d" }, 3] }, "e": 2 };
+        let mut doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert_eq!(
+            doc.remove_path("a.b.1.c").unwrap().unwrap().as_str(),
+            Some("d")
+        );
+        assert_eq!(doc.remove_path("a.b.0").unwrap().unwrap().as_i32(), Some(1));
+        assert!(doc.remove_path("a.b.5").unwrap().is_none());
+        assert!(doc.remove_path("e.f").unwrap().is_none());
+        assert!(doc.remove_path("z.y").unwrap().is_none());
+        assert_eq!(doc.remove_path("e").unwrap().unwrap().as_i32(), Some(2));
+        assert_eq!(
+            doc_to_vec(&doc),
+            rawdoc! { "a": { "b": [{}, 3] } }.as_bytes()
+        );
+    }
+}
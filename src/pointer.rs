@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+
+use crate::{MutableDocument, MutableValue, ParsedDocument, path::child_with_index};
+
+/// Errors that may occur when accessing a document using a JSON Pointer (RFC 6901).
+#[derive(Clone, Debug)]
+pub enum PointerError {
+    /// The pointer is not empty and does not begin with `/`, or contains a `~` that is not part
+    /// of a `~0` or `~1` escape.
+    Malformed(String),
+    /// The empty pointer refers to the whole document, which cannot be accessed as a value.
+    Root,
+    /// An intermediate document or array could not be parsed.
+    Raw(bson::raw::Error),
+    /// The pointer prefix does not refer to an existing value.
+    NotFound(String),
+    /// The pointer prefix refers to a value that is not a document or array.
+    NotTraversable(String),
+    /// The pointer refers to an array and the last token is not a valid index.
+    InvalidArrayIndex(String),
+}
+
+impl std::fmt::Display for PointerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed(p) => write!(f, "{} is not a valid JSON pointer", p),
+            Self::Root => write!(f, "the empty pointer refers to the whole document"),
+            Self::Raw(e) => write!(f, "{}", e),
+            Self::NotFound(p) => write!(f, "no value at {}", p),
+            Self::NotTraversable(p) => write!(f, "value at {} is not a document or array", p),
+            Self::InvalidArrayIndex(p) => write!(f, "{} is not a valid array index", p),
+        }
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+impl From<bson::raw::Error> for PointerError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+/// Split pointer into unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<Cow<'_, str>>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let malformed = || PointerError::Malformed(pointer.to_string());
+    pointer
+        .strip_prefix('/')
+        .ok_or_else(malformed)?
+        .split('/')
+        .map(|t| unescape(t).ok_or_else(malformed))
+        .collect()
+}
+
+fn unescape(token: &str) -> Option<Cow<'_, str>> {
+    if !token.contains('~') {
+        return Some(token.into());
+    }
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => out.push('~'),
+                Some('1') => out.push('/'),
+                _ => return None,
+            },
+            c => out.push(c),
+        }
+    }
+    Some(out.into())
+}
+
+/// Returns the prefix of pointer ending after token i.
+pub(crate) fn pointer_prefix(pointer: &str, i: usize) -> String {
+    pointer
+        .match_indices('/')
+        .nth(i + 1)
+        .map_or(pointer, |(end, _)| &pointer[..end])
+        .to_string()
+}
+
+/// Parse an array index token, which may not have leading zeros.
+pub(crate) fn pointer_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || (token.len() > 1 && token.starts_with('0'))
+        || !token.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// A parsed container that a pointer token may be resolved against.
+pub(crate) enum Parent<'d, 'a> {
+    Document(&'d mut ParsedDocument<'a>),
+    Array(&'d mut Vec<MutableValue<'a>>),
+}
+
+/// Resolve tokens from doc to a container, parsing any encoded documents and arrays.
+pub(crate) fn parent_mut<'d, 'a>(
+    doc: &'d mut ParsedDocument<'a>,
+    pointer: &str,
+    tokens: &[Cow<'_, str>],
+) -> Result<Parent<'d, 'a>, PointerError> {
+    let mut parent = Parent::Document(doc);
+    for (i, token) in tokens.iter().enumerate() {
+        let value = match parent {
+            Parent::Document(d) => d.get_mut(token),
+            Parent::Array(a) => pointer_index(token).and_then(|i| a.get_mut(i)),
+        }
+        .ok_or_else(|| PointerError::NotFound(pointer_prefix(pointer, i)))?;
+        parent = match value {
+            MutableValue::Document(d) => Parent::Document(d.to_parsed()?),
+            MutableValue::Array(a) => Parent::Array(a.to_parsed()?),
+            _ => return Err(PointerError::NotTraversable(pointer_prefix(pointer, i))),
+        };
+    }
+    Ok(parent)
+}
+
+fn get_tokens<'v, 'a>(
+    first: Option<Cow<'v, MutableValue<'a>>>,
+    rest: &[Cow<'_, str>],
+) -> Option<Cow<'v, MutableValue<'a>>> {
+    let mut value = first?;
+    for token in rest {
+        value = child_with_index(value, token, pointer_index)?;
+    }
+    Some(value)
+}
+
+impl<'a> ParsedDocument<'a> {
+    /// Get the value referred to by a JSON Pointer (RFC 6901) like `"/a/0/b"`, where numeric
+    /// tokens index into arrays.
+    ///
+    /// Like [`ParsedDocument::get_path`] values are returned by reference if they have been
+    /// parsed. Fails if the pointer is malformed or empty.
+    pub fn get_pointer(
+        &self,
+        pointer: &str,
+    ) -> Result<Option<Cow<'_, MutableValue<'a>>>, PointerError> {
+        let tokens = parse_pointer(pointer)?;
+        let (first, rest) = tokens.split_first().ok_or(PointerError::Root)?;
+        Ok(get_tokens(
+            self.get(first.as_ref()).map(Cow::Borrowed),
+            rest,
+        ))
+    }
+
+    /// Set the value referred to by a JSON Pointer (RFC 6901) and return the previous value if
+    /// any.
+    ///
+    /// The parent of the target must exist. If the parent is an array the last token must be an
+    /// index no greater than the array length or `-`, both of which append to the array. Any raw
+    /// documents or arrays along the pointer are parsed.
+    pub fn set_pointer<V: Into<MutableValue<'static>>>(
+        &mut self,
+        pointer: &str,
+        value: V,
+    ) -> Result<Option<MutableValue<'a>>, PointerError> {
+        let tokens = parse_pointer(pointer)?;
+        let (last, parents) = tokens.split_last().ok_or(PointerError::Root)?;
+        match parent_mut(self, pointer, parents)? {
+            Parent::Document(d) => Ok(d.insert(last.as_ref(), value)),
+            Parent::Array(a) => {
+                let index = match last.as_ref() {
+                    "-" => a.len(),
+                    token => pointer_index(token)
+                        .filter(|i| *i <= a.len())
+                        .ok_or_else(|| PointerError::InvalidArrayIndex(pointer.to_string()))?,
+                };
+                if index < a.len() {
+                    Ok(Some(std::mem::replace(&mut a[index], value.into())))
+                } else {
+                    a.push(value.into());
+                    Ok(None)
+                }
+            }
+        }
+    }
+
+    /// Remove the value referred to by a JSON Pointer (RFC 6901) and return it if present.
+    ///
+    /// Any raw documents or arrays along the pointer are parsed. Removing an array element shifts
+    /// all subsequent elements. If the pointer does not refer to a value this returns `Ok(None)`.
+    pub fn remove_pointer(
+        &mut self,
+        pointer: &str,
+    ) -> Result<Option<MutableValue<'a>>, PointerError> {
+        let tokens = parse_pointer(pointer)?;
+        let (last, parents) = tokens.split_last().ok_or(PointerError::Root)?;
+        match parent_mut(self, pointer, parents) {
+            Ok(Parent::Document(d)) => Ok(d.remove(last.as_ref())),
+            Ok(Parent::Array(a)) => Ok(pointer_index(last)
+                .filter(|i| *i < a.len())
+                .map(|i| a.remove(i))),
+            Err(PointerError::NotFound(_) | PointerError::NotTraversable(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'a> MutableDocument<'a> {
+    /// Get the value referred to by a JSON Pointer (RFC 6901) without parsing the document.
+    ///
+    /// See [`ParsedDocument::get_pointer`]. Malformed nested raw values are treated as missing.
+    pub fn get_pointer(
+        &self,
+        pointer: &str,
+    ) -> Result<Option<Cow<'_, MutableValue<'a>>>, PointerError> {
+        let tokens = parse_pointer(pointer)?;
+        let (first, rest) = tokens.split_first().ok_or(PointerError::Root)?;
+        Ok(get_tokens(self.get(first.as_ref())?, rest))
+    }
+
+    /// Set the value referred to by a JSON Pointer (RFC 6901), parsing the document if necessary.
+    ///
+    /// See [`ParsedDocument::set_pointer`].
+    pub fn set_pointer<V: Into<MutableValue<'static>>>(
+        &mut self,
+        pointer: &str,
+        value: V,
+    ) -> Result<Option<MutableValue<'a>>, PointerError> {
+        self.to_parsed()?.set_pointer(pointer, value)
+    }
+
+    /// Remove the value referred to by a JSON Pointer (RFC 6901), parsing the document if
+    /// necessary.
+    ///
+    /// See [`ParsedDocument::remove_pointer`].
+    pub fn remove_pointer(
+        &mut self,
+        pointer: &str,
+    ) -> Result<Option<MutableValue<'a>>, PointerError> {
+        self.to_parsed()?.remove_pointer(pointer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableDocument, ParsedDocument};
+
+    use super::PointerError;
+
+    #[test]
+    fn get_pointer() {
+        let raw = rawdoc! { "a": { "b": [1, { "c/d": "x", "e~f": 2.5 }] }, "": 2i64 };
+        let doc = MutableDocument::from(raw.as_ref());
+        let parsed = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        for (pointer, expected) in [
+            ("/a/b/0", Some("Int32")),
+            ("/a/b/1/c~1d", Some("String")),
+            ("/a/b/1/e~0f", Some("Double")),
+            ("/", Some("Int64")),
+            ("/a/b/2", None),
+            ("/a/b/01", None),
+            ("/a/b/+1", None),
+            ("/z", None),
+        ] {
+            assert_eq!(
+                doc.get_pointer(pointer).unwrap().map(|v| v.type_name()),
+                expected
+            );
+            assert_eq!(
+                parsed.get_pointer(pointer).unwrap().map(|v| v.type_name()),
+                expected
+            );
+        }
+        assert!(matches!(doc.get_pointer(""), Err(PointerError::Root)));
+        assert!(matches!(
+            parsed.get_pointer("a/b"),
+            Err(PointerError::Malformed(_))
+        ));
+        assert!(matches!(
+            parsed.get_pointer("/a~2"),
+            Err(PointerError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn set_pointer() {
+        let raw = rawdoc! { "a": { "b": [1, 2] }, "c": 3 };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(doc.set_pointer("/c", 4).unwrap().unwrap().as_i32(), Some(3));
+        assert_eq!(
+            doc.set_pointer("/a/b/0", 5).unwrap().unwrap().as_i32(),
+            Some(1)
+        );
+        assert!(doc.set_pointer("/a/b/2", 6).unwrap().is_none());
+        assert!(doc.set_pointer("/a/b/-", 7).unwrap().is_none());
+        assert!(doc.set_pointer("/a/d", 8).unwrap().is_none());
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": { "b": [5, 2, 6, 7], "d": 8 }, "c": 4 }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn set_pointer_invalid() {
+        let raw = rawdoc! { "a": { "b": [1, 2] }, "c": 3 };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert!(matches!(
+            doc.set_pointer("/x/y", 1),
+            Err(PointerError::NotFound(p)) if p == "/x"
+        ));
+        assert!(matches!(
+            doc.set_pointer("/c/y", 1),
+            Err(PointerError::NotTraversable(p)) if p == "/c"
+        ));
+        assert!(matches!(
+            doc.set_pointer("/a/b/3", 1),
+            Err(PointerError::InvalidArrayIndex(p)) if p == "/a/b/3"
+        ));
+        assert!(matches!(
+            doc.set_pointer("/a/b/01", 1),
+            Err(PointerError::InvalidArrayIndex(_))
+        ));
+        assert!(matches!(doc.set_pointer("", 1), Err(PointerError::Root)));
+    }
+
+    #[test]
+    fn remove_pointer() {
+        let raw = rawdoc! { "a": { "b": [1, 2, 3] }, "c": 3 };
+        let mut doc = MutableDocument::from(raw.as_ref());
+        assert_eq!(
+            doc.remove_pointer("/a/b/1").unwrap().unwrap().as_i32(),
+            Some(2)
+        );
+        assert_eq!(doc.remove_pointer("/c").unwrap().unwrap().as_i32(), Some(3));
+        assert!(doc.remove_pointer("/a/b/5").unwrap().is_none());
+        assert!(doc.remove_pointer("/x/y").unwrap().is_none());
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": { "b": [1, 3] } }.as_bytes()
+        );
+    }
+}
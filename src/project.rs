@@ -0,0 +1,248 @@
+use crate::{MutableValue, ParsedDocument, PathError};
+
+/// Errors that may occur when applying a projection with [`ParsedDocument::project()`].
+#[derive(Clone, Debug)]
+pub enum ProjectionError {
+    /// The projection includes some fields and excludes others. Only `_id` may be excluded from
+    /// an inclusion projection.
+    MixedInclusionExclusion,
+    /// The projection value for this path is not a number or boolean.
+    InvalidValue(String),
+    /// This path overlaps with another included path, like `"a"` and `"a.b"`.
+    PathCollision(String),
+    /// A path could not be traversed.
+    Path(PathError),
+    /// A document or array could not be parsed.
+    Raw(bson::raw::Error),
+}
+
+impl std::fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MixedInclusionExclusion => {
+                write!(f, "projection cannot mix inclusion and exclusion")
+            }
+            Self::InvalidValue(p) => write!(f, "invalid projection value for {}", p),
+            Self::PathCollision(p) => write!(f, "projection path collision at {}", p),
+            Self::Path(e) => write!(f, "{}", e),
+            Self::Raw(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProjectionError {}
+
+impl From<bson::raw::Error> for ProjectionError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl From<PathError> for ProjectionError {
+    fn from(value: PathError) -> Self {
+        match value {
+            PathError::Raw(e) => Self::Raw(e),
+            e => Self::Path(e),
+        }
+    }
+}
+
+const ID: &str = "_id";
+
+impl<'a> ParsedDocument<'a> {
+    /// Apply a MongoDB-style projection where `1` (or `true`) includes a dot-notation path and
+    /// `0` (or `false`) excludes it.
+    ///
+    /// An inclusion projection returns only the included paths, plus `_id` unless it is excluded.
+    /// A projection that only includes `_id` is an inclusion projection.
+    /// `_id` is placed first and other paths follow in projection order. An exclusion projection
+    /// returns this document with the excluded paths removed. Missing paths are ignored.
+    pub fn project(
+        &self,
+        projection: &ParsedDocument<'_>,
+    ) -> Result<ParsedDocument<'a>, ProjectionError> {
+        let mut include_id = true;
+        let mut explicitly_include_id = false;
+        let mut include = vec![];
+        let mut exclude = vec![];
+        for (path, value) in projection.iter() {
+            let included = match value {
+                MutableValue::Boolean(b) => *b,
+                v => {
+                    v.as_number_f64()
+                        .ok_or_else(|| ProjectionError::InvalidValue(path.to_string()))?
+                        != 0.0
+                }
+            };
+            if path == ID {
+                include_id = included;
+                explicitly_include_id = included;
+            } else if included {
+                include.push(path);
+            } else {
+                exclude.push(path);
+            }
+        }
+
+        if !include.is_empty() && !exclude.is_empty() {
+            return Err(ProjectionError::MixedInclusionExclusion);
+        }
+
+        if include.is_empty() && !(exclude.is_empty() && explicitly_include_id) {
+            let mut projected = self.clone();
+            if !include_id {
+                projected.remove(ID);
+            }
+            for path in exclude {
+                projected.remove_path(path)?;
+            }
+            return Ok(projected);
+        }
+
+        let mut projected = ParsedDocument::new();
+        if include_id && let Some(id) = self.get(ID) {
+            projected.extend([(ID, id.clone())]);
+        }
+        for path in include {
+            if let Some(value) = self.get_path(path) {
+                insert_path(&mut projected, path, value.into_owned())?;
+            }
+        }
+        Ok(projected)
+    }
+
+    /// Returns a shallow copy of this document without the top-level fields in keys.
+    ///
+    /// Keys and nested encoded values continue to borrow from the source.
+    pub fn without_keys(&self, keys: &[&str]) -> ParsedDocument<'a> {
+        self.filter_keys(|k| !keys.contains(&k))
+    }
+
+    /// Returns a shallow copy of this document with only the top-level fields in keys, in the order
+    /// they appear in this document.
+    ///
+    /// Keys and nested encoded values continue to borrow from the source.
+    pub fn with_keys_only(&self, keys: &[&str]) -> ParsedDocument<'a> {
+        self.filter_keys(|k| keys.contains(&k))
+    }
+
+    fn filter_keys(&self, keep: impl Fn(&str) -> bool) -> ParsedDocument<'a> {
+        let mut filtered = ParsedDocument::new();
+        for (key, value) in self.fields_iter().filter(|(k, _)| keep(k)) {
+            filtered.insert_borrowed(key.clone(), value.clone());
+        }
+        filtered
+    }
+}
+
+/// Insert value at a dot-notation path in doc, creating intermediate documents.
+fn insert_path<'a>(
+    mut doc: &mut ParsedDocument<'a>,
+    path: &str,
+    value: MutableValue<'a>,
+) -> Result<(), ProjectionError> {
+    let (parents, last) = match path.rsplit_once('.') {
+        Some((parents, last)) => (Some(parents), last),
+        None => (None, path),
+    };
+    for segment in parents.into_iter().flat_map(|p| p.split('.')) {
+        doc = match doc.get_or_insert_with(segment, || ParsedDocument::new().into()) {
+            MutableValue::Document(d) => d.to_parsed()?,
+            _ => return Err(ProjectionError::PathCollision(path.to_string())),
+        };
+    }
+    if doc.contains_key(last) {
+        return Err(ProjectionError::PathCollision(path.to_string()));
+    }
+    doc.extend([(last, value)]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::ParsedDocument;
+
+    use super::ProjectionError;
+
+    fn source() -> bson::RawDocumentBuf {
+        rawdoc! { "_id": 1, "a": { "b": 2, "c": 3 }, "d": "e", "f": [4] }
+    }
+
+    #[test]
+    fn inclusion() {
+        let raw = source();
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        let projected = doc
+            .project(&parsed_doc! { "d": 1, "a.c": true, "missing": 1 })
+            .unwrap();
+        assert_eq!(
+            projected.to_vec().unwrap(),
+            rawdoc! { "_id": 1, "d": "e", "a": { "c": 3 } }.as_bytes()
+        );
+
+        let projected = doc.project(&parsed_doc! { "_id": 0, "f": 1 }).unwrap();
+        assert_eq!(projected.to_vec().unwrap(), rawdoc! { "f": [4] }.as_bytes());
+
+        let projected = doc.project(&parsed_doc! { "_id": 1 }).unwrap();
+        assert_eq!(projected.to_vec().unwrap(), rawdoc! { "_id": 1 }.as_bytes());
+    }
+
+    #[test]
+    fn exclusion() {
+        let raw = source();
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        let projected = doc
+            .project(&parsed_doc! { "a.b": 0, "f": false, "_id": 0 })
+            .unwrap();
+        assert_eq!(
+            projected.to_vec().unwrap(),
+            rawdoc! { "a": { "c": 3 }, "d": "e" }.as_bytes()
+        );
+        assert_eq!(
+            doc.project(&parsed_doc! {}).unwrap().to_vec().unwrap(),
+            raw.as_bytes()
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let raw = source();
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert!(matches!(
+            doc.project(&parsed_doc! { "a": 1, "d": 0 }),
+            Err(ProjectionError::MixedInclusionExclusion)
+        ));
+        assert!(matches!(
+            doc.project(&parsed_doc! { "a": "yes" }),
+            Err(ProjectionError::InvalidValue(p)) if p == "a"
+        ));
+        assert!(matches!(
+            doc.project(&parsed_doc! { "a": 1, "a.b": 1 }),
+            Err(ProjectionError::PathCollision(p)) if p == "a.b"
+        ));
+    }
+
+    #[test]
+    fn without_keys() {
+        let raw = source();
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(
+            doc.without_keys(&["a", "f", "missing"]).to_vec().unwrap(),
+            rawdoc! { "_id": 1, "d": "e" }.as_bytes()
+        );
+        assert_eq!(doc.without_keys(&[]).to_vec().unwrap(), raw.as_bytes());
+    }
+
+    #[test]
+    fn with_keys_only() {
+        let raw = source();
+        let doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(
+            doc.with_keys_only(&["f", "a", "missing"]).to_vec().unwrap(),
+            rawdoc! { "a": { "b": 2, "c": 3 }, "f": [4] }.as_bytes()
+        );
+        assert!(doc.with_keys_only(&[]).is_empty());
+    }
+}
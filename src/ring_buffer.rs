@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+
+use crate::{ParsedDocument, write::invalid_data_ser_error};
+
+/// Keeps the most recent documents pushed up to a fixed capacity, like a history for undo.
+///
+/// Documents are stored encoded for compactness and parsed when read. Index 0 is the oldest
+/// document retained.
+#[derive(Clone, Debug)]
+pub struct DocumentRingBuffer {
+    docs: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+impl DocumentRingBuffer {
+    /// Create an empty buffer that retains up to capacity documents.
+    ///
+    /// Panics if capacity is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            docs: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of documents retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of documents retained.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Returns true if no documents have been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Encode and append doc, discarding the oldest document if the buffer is full.
+    ///
+    /// Nested encoded documents and arrays are parsed so that malformed values are rejected here
+    /// rather than when the document is read. Fails if doc is malformed or cannot be encoded, in
+    /// which case the buffer is unchanged.
+    pub fn push(&mut self, doc: ParsedDocument<'_>) -> Result<(), bson::ser::Error> {
+        let encoded = doc.into_owned().map_err(invalid_data_ser_error)?.to_vec()?;
+        if self.docs.len() == self.capacity {
+            self.docs.pop_front();
+        }
+        self.docs.push_back(encoded);
+        Ok(())
+    }
+
+    /// Parse and return the document at index, where 0 is the oldest document.
+    pub fn get(&self, index: usize) -> Option<ParsedDocument<'static>> {
+        self.docs
+            .get(index)
+            .map(|d| parse(d).into_owned().expect("document encoded by push()"))
+    }
+
+    /// Iterate over the documents from oldest to newest, parsing each document as it is visited.
+    ///
+    /// Documents borrow strings and nested documents from the buffer.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = ParsedDocument<'_>> {
+        self.docs.iter().map(|d| parse(d))
+    }
+}
+
+fn parse(encoded: &[u8]) -> ParsedDocument<'_> {
+    ParsedDocument::try_from(encoded).expect("document encoded by push()")
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::ParsedDocument;
+
+    use super::DocumentRingBuffer;
+
+    #[test]
+    fn push_and_get() {
+        let mut history = DocumentRingBuffer::new(2);
+        assert!(history.is_empty());
+        assert!(history.get(0).is_none());
+        for i in 0..3 {
+            history.push(parsed_doc! { "v": i, "s": "x" }).unwrap();
+        }
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.get(0).unwrap().get_i32("v"), Some(1));
+        assert_eq!(history.get(1).unwrap().get_i32("v"), Some(2));
+        assert!(history.get(2).is_none());
+        assert_eq!(
+            history
+                .iter()
+                .rev()
+                .map(|d| d.get_i32("v").unwrap())
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+    }
+
+    #[test]
+    fn push_invalid() {
+        let mut history = DocumentRingBuffer::new(1);
+        history.push(parsed_doc! { "a": 1 }).unwrap();
+        assert!(history.push(parsed_doc! { "a\0": 2 }).is_err());
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.get(0).unwrap().get_i32("a"), Some(1));
+    }
+
+    #[test]
+    fn push_malformed_nested() {
+        let mut bytes = rawdoc! { "a": { "b": 1 } }.into_bytes();
+        // Skip the document length, the type byte and key of "a", and the nested document length
+        // to corrupt the type byte of "b".
+        bytes[4 + 1 + 2 + 4] = 0x55;
+        let doc = ParsedDocument::try_from(bytes.as_slice()).unwrap();
+        let mut history = DocumentRingBuffer::new(1);
+        assert!(history.push(doc).is_err());
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be positive")]
+    fn zero_capacity() {
+        DocumentRingBuffer::new(0);
+    }
+}
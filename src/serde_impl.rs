@@ -0,0 +1,197 @@
+use bson::{Bson, Document, RawBsonRef};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    ser::{SerializeMap, SerializeSeq, SerializeStruct},
+};
+
+use crate::{
+    MutableArray, MutableBinary, MutableDbPointer, MutableDocument, MutableJavaScriptCodeWithScope,
+    MutableRegex, MutableValue, ParsedDocument,
+};
+
+/// Serializes using the same serde data model as [`bson::Bson`].
+impl Serialize for MutableValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Values that borrow or are fixed size are serialized as the equivalent RawBsonRef.
+        let raw = match self {
+            Self::Double(v) => RawBsonRef::Double(*v),
+            Self::String(v) => RawBsonRef::String(v),
+            Self::Document(v) => return v.serialize(serializer),
+            Self::Array(v) => return v.serialize(serializer),
+            Self::Binary(MutableBinary::Borrowed(v)) => RawBsonRef::Binary(*v),
+            Self::Binary(MutableBinary::Owned(v)) => RawBsonRef::from(v),
+            Self::Undefined => RawBsonRef::Undefined,
+            Self::ObjectId(v) => RawBsonRef::ObjectId(*v),
+            Self::Boolean(v) => RawBsonRef::Boolean(*v),
+            Self::DateTime(v) => RawBsonRef::DateTime(*v),
+            Self::Null => RawBsonRef::Null,
+            Self::RegularExpression(MutableRegex::Borrowed(v)) => RawBsonRef::RegularExpression(*v),
+            Self::RegularExpression(MutableRegex::Owned(v)) => return v.serialize(serializer),
+            Self::DbPointer(MutableDbPointer::Borrowed(v)) => RawBsonRef::DbPointer(*v),
+            Self::DbPointer(MutableDbPointer::Owned(v)) => return v.serialize(serializer),
+            Self::JavaScriptCode(v) => RawBsonRef::JavaScriptCode(v),
+            Self::Symbol(v) => RawBsonRef::Symbol(v),
+            Self::JavaScriptCodeWithScope(v) => return v.serialize(serializer),
+            Self::Int32(v) => RawBsonRef::Int32(*v),
+            Self::Timestamp(v) => RawBsonRef::Timestamp(*v),
+            Self::Int64(v) => RawBsonRef::Int64(*v),
+            Self::Decimal128(v) => RawBsonRef::Decimal128(*v),
+            Self::MinKey => RawBsonRef::MinKey,
+            Self::MaxKey => RawBsonRef::MaxKey,
+        };
+        raw.serialize(serializer)
+    }
+}
+
+/// Serializes as a map with entries in document order.
+impl Serialize for ParsedDocument<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (k, v) in self.iter() {
+            map.serialize_entry(k, v)?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for MutableDocument<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Borrowed(v) => v.serialize(serializer),
+            Self::Owned(v) => v.serialize(serializer),
+        }
+    }
+}
+
+/// Serializes as a sequence.
+impl Serialize for MutableArray<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Borrowed(v) => v.serialize(serializer),
+            Self::Owned(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for e in v.iter() {
+                    seq.serialize_element(e)?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+impl Serialize for MutableJavaScriptCodeWithScope<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Borrowed(v) => v.serialize(serializer),
+            Self::Owned(v) => v.serialize(serializer),
+            Self::Parsed { code, scope } => {
+                let mut state = serializer.serialize_struct("$codeWithScope", 2)?;
+                state.serialize_field("$code", code)?;
+                state.serialize_field("$scope", scope)?;
+                state.end()
+            }
+        }
+    }
+}
+
+/// Deserializes from the same serde data model as [`bson::Bson`].
+///
+/// The value is decoded into a [`bson::Bson`] first so all strings and byte slices are owned.
+impl<'de> Deserialize<'de> for MutableValue<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Bson::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Deserializes from a map using the same serde data model as [`bson::Document`].
+///
+/// The value is decoded into a [`bson::Document`] first so all strings and byte slices are owned.
+impl<'de> Deserialize<'de> for ParsedDocument<'static> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Document::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{
+        Binary, Bson, DateTime, Document, JavaScriptCodeWithScope, Regex, Timestamp, doc,
+        oid::ObjectId, spec::BinarySubtype, to_raw_document_buf,
+    };
+
+    use crate::{MutableDocument, MutableValue, ParsedDocument};
+
+    fn doc_many_types() -> Document {
+        doc! {
+            "a": 1.5,
+            "b": "str",
+            "c": { "d": [1, "two", { "e": 3i64 }] },
+            "f": Binary { subtype: BinarySubtype::Generic, bytes: vec![1, 2, 3] },
+            "g": Bson::Undefined,
+            "h": ObjectId::from_bytes([0xae; 12]),
+            "i": DateTime::from_millis(1234567890),
+            "j": Bson::Null,
+            "k": Regex { pattern: "foo.*".into(), options: "i".into() },
+            "m": Bson::JavaScriptCode("some code".into()),
+            "n": Bson::Symbol("sym".into()),
+            "o": JavaScriptCodeWithScope { code: "code".into(), scope: doc! { "x": 1 } },
+            "q": Timestamp { time: 1234567890, increment: 2 },
+            "t": Bson::MinKey,
+            "u": Bson::MaxKey,
+        }
+    }
+
+    #[test]
+    fn serialize_json() {
+        let expected = serde_json::to_value(Bson::Document(doc_many_types())).unwrap();
+        let raw = to_raw_document_buf(&doc_many_types()).unwrap();
+        let borrowed = MutableDocument::from(raw.as_ref());
+        assert_eq!(serde_json::to_value(&borrowed).unwrap(), expected);
+
+        let mut parsed = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        for v in parsed.values_mut() {
+            if let Some(d) = v.as_doc_mut() {
+                d.to_parsed().unwrap();
+            }
+        }
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), expected);
+        assert_eq!(
+            serde_json::to_value(MutableValue::from(Bson::Document(doc_many_types()))).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn deserialize_json_round_trip() {
+        // Int64 and generic binary values do not survive a round trip through JSON.
+        let mut doc = doc_many_types();
+        doc.remove("c");
+        doc.remove("f");
+        let raw = to_raw_document_buf(&doc).unwrap();
+        let json = serde_json::to_string(&MutableDocument::from(raw.as_ref())).unwrap();
+
+        let doc: ParsedDocument<'static> = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc.to_vec().unwrap(), raw.as_bytes());
+
+        let value: MutableValue<'static> = serde_json::from_str(&json).unwrap();
+        assert_eq!(value.as_doc().unwrap().to_vec().unwrap(), raw.as_bytes());
+    }
+
+    #[test]
+    fn deserialize_bson() {
+        let raw = to_raw_document_buf(&doc_many_types()).unwrap();
+        let doc: ParsedDocument<'static> = bson::from_slice(raw.as_bytes()).unwrap();
+        assert_eq!(doc.to_vec().unwrap(), raw.as_bytes());
+    }
+
+    #[test]
+    fn serialize_bson() {
+        let raw = to_raw_document_buf(&doc_many_types()).unwrap();
+        let parsed = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        assert_eq!(bson::to_vec(&parsed).unwrap(), raw.as_bytes());
+        assert_eq!(
+            bson::to_vec(&ParsedDocument::from(doc_many_types())).unwrap(),
+            raw.as_bytes()
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    MutableArray, MutableDocument, MutableJavaScriptCodeWithScope, MutableValue, ParsedDocument,
+};
+
+impl ParsedDocument<'_> {
+    /// Returns a deep copy of the document with keys sorted at every nesting level, including
+    /// nested documents within arrays and the scopes of code with scope values.
+    ///
+    /// This is useful to compare or hash documents regardless of field order. May fail with a raw
+    /// BSON parsing error if a nested encoded document is malformed.
+    pub fn sorted_clone(&self) -> Result<ParsedDocument<'static>, bson::raw::Error> {
+        let mut fields = self.iter().collect::<Vec<_>>();
+        fields.sort_by(|a, b| a.0.cmp(b.0));
+        fields
+            .into_iter()
+            .map(|(k, v)| Ok((k.to_string(), sorted_value(v)?)))
+            .collect()
+    }
+}
+
+/// Returns a deep copy of doc with keys sorted at every nesting level.
+pub(crate) fn sorted_document(
+    doc: &MutableDocument<'_>,
+) -> Result<ParsedDocument<'static>, bson::raw::Error> {
+    match doc {
+        MutableDocument::Borrowed(_) => {
+            let mut fields = doc.iter().collect::<Result<Vec<_>, _>>()?;
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            fields
+                .into_iter()
+                .map(|(k, v)| Ok((k.to_string(), sorted_value(&v)?)))
+                .collect()
+        }
+        MutableDocument::Owned(p) => p.sorted_clone(),
+    }
+}
+
+fn sorted_value(value: &MutableValue<'_>) -> Result<MutableValue<'static>, bson::raw::Error> {
+    Ok(match value {
+        MutableValue::Document(d) => MutableValue::Document(sorted_document(d)?.into()),
+        MutableValue::Array(a) => MutableValue::Array(MutableArray::Owned(
+            a.iter()
+                .map(|v| sorted_value(v?.as_ref()))
+                .collect::<Result<_, _>>()?,
+        )),
+        MutableValue::JavaScriptCodeWithScope(v) => {
+            let scope = match v {
+                MutableJavaScriptCodeWithScope::Borrowed(v) => {
+                    ParsedDocument::try_from(v.scope)?.sorted_clone()?
+                }
+                MutableJavaScriptCodeWithScope::Owned(v) => {
+                    ParsedDocument::from(v.scope.clone()).sorted_clone()?
+                }
+                MutableJavaScriptCodeWithScope::Parsed { scope, .. } => scope.sorted_clone()?,
+            };
+            MutableValue::JavaScriptCodeWithScope(MutableJavaScriptCodeWithScope::Parsed {
+                code: v.code().to_owned().into(),
+                scope,
+            })
+        }
+        v => v.clone().into_owned()?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{JavaScriptCodeWithScope, doc, rawdoc};
+
+    use crate::{MutableValue, ParsedDocument};
+
+    #[test]
+    fn sorted_clone() {
+        let raw = rawdoc! { "b": { "d": 1, "c": [{ "f": 1, "e": 2 }] }, "a": 1 };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        doc.insert(
+            "c",
+            MutableValue::from(bson::Bson::JavaScriptCodeWithScope(
+                JavaScriptCodeWithScope {
+                    code: "x".into(),
+                    scope: doc! { "z": 1, "y": { "q": 1, "p": 2 } },
+                },
+            )),
+        );
+        let sorted = doc.sorted_clone().unwrap();
+        assert_eq!(
+            sorted.try_into_document().unwrap(),
+            doc! {
+                "a": 1,
+                "b": { "c": [{ "e": 2, "f": 1 }], "d": 1 },
+                "c": JavaScriptCodeWithScope {
+                    code: "x".into(),
+                    scope: doc! { "y": { "p": 2, "q": 1 }, "z": 1 },
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn sorted_clone_encoded_scope() {
+        let code = JavaScriptCodeWithScope {
+            code: "x".into(),
+            scope: doc! { "z": 1, "y": 2 },
+        };
+        let bytes = bson::to_vec(&doc! { "c": code }).unwrap();
+        let doc = ParsedDocument::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(
+            doc.sorted_clone().unwrap().try_into_document().unwrap(),
+            doc! {
+                "c": JavaScriptCodeWithScope {
+                    code: "x".into(),
+                    scope: doc! { "y": 2, "z": 1 },
+                },
+            }
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+use crate::{MutableDocument, ParsedDocument};
+
+/// Errors that may occur when reading documents from a [`std::io::Read`].
+#[derive(Clone, Debug)]
+pub enum ReadError {
+    /// The underlying reader failed or ended in the middle of a document.
+    Io(Arc<io::Error>),
+    /// The length prefix of a document is smaller than the minimum document length.
+    InvalidLength(i32),
+    /// A document could not be parsed.
+    Raw(bson::raw::Error),
+}
+
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::InvalidLength(len) => write!(f, "invalid document length {}", len),
+            Self::Raw(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(Arc::new(value))
+    }
+}
+
+impl From<bson::raw::Error> for ReadError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+// 4 bytes for length, 1 byte for null terminator.
+const MIN_DOCUMENT_LEN: i32 = 5;
+
+/// Read the rest of a document with the given length prefix from reader and parse it.
+fn read_document<R: Read>(
+    reader: &mut R,
+    prefix: [u8; 4],
+) -> Result<ParsedDocument<'static>, ReadError> {
+    let len = i32::from_le_bytes(prefix);
+    if len < MIN_DOCUMENT_LEN {
+        return Err(ReadError::InvalidLength(len));
+    }
+    // Grow the buffer as bytes arrive rather than trusting the length prefix for allocation.
+    let mut buf = prefix.to_vec();
+    reader.take(len as u64 - 4).read_to_end(&mut buf)?;
+    if buf.len() != len as usize {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(ParsedDocument::try_from(buf.as_slice())?.into_owned()?)
+}
+
+impl ParsedDocument<'_> {
+    /// Read a single encoded document from reader and parse it.
+    ///
+    /// Reads exactly the number of bytes in the document's length prefix. Fails if reader fails
+    /// or ends before the end of the document, or if the document is malformed. Errors are
+    /// reported as [`ReadError`] rather than [`bson::raw::Error`], which cannot represent I/O
+    /// failures.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<ParsedDocument<'static>, ReadError> {
+        let mut prefix = [0u8; 4];
+        reader.read_exact(&mut prefix)?;
+        read_document(&mut reader, prefix)
+    }
+}
+
+/// Reads a sequence of encoded documents written back to back, like the output of `mongodump`.
+///
+/// Iteration ends at the end of the input, or after yielding the first error.
+pub struct BsonStreamReader<R> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> BsonStreamReader<R> {
+    /// Create a reader that reads documents from reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    /// Returns the underlying reader, positioned after the last bytes read.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Read the length prefix of the next document, or `None` at the end of the input.
+    fn read_prefix(&mut self) -> io::Result<Option<[u8; 4]>> {
+        let mut prefix = [0u8; 4];
+        let mut read = 0;
+        while read < prefix.len() {
+            match self.reader.read(&mut prefix[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Some(prefix))
+    }
+}
+
+impl<R: Read> Iterator for BsonStreamReader<R> {
+    type Item = Result<ParsedDocument<'static>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.read_prefix() {
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Ok(Some(prefix)) => read_document(&mut self.reader, prefix),
+            Err(e) => Err(e.into()),
+        };
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+/// Writes a sequence of encoded documents back to back, the format read by [`BsonStreamReader`].
+pub struct BsonStreamWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> BsonStreamWriter<W> {
+    /// Create a writer that writes documents to writer.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Returns the underlying writer without flushing it.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write doc to the stream.
+    ///
+    /// Fails if the document exceeds the default maximum document size, cannot be encoded, or if
+    /// the writer fails. The stream may contain a partial document after a failure.
+    pub fn write_document(&mut self, doc: &MutableDocument<'_>) -> io::Result<()> {
+        doc.write_to(&mut self.writer)
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Cursor};
+
+    use bson::rawdoc;
+
+    use crate::{MutableDocument, ParsedDocument};
+
+    use super::{BsonStreamReader, BsonStreamWriter, ReadError};
+
+    #[test]
+    fn from_reader() {
+        let raw = rawdoc! { "a": 1, "b": { "c": [2, "d"] } };
+        let mut bytes = raw.as_bytes().to_vec();
+        bytes.extend_from_slice(b"trailing");
+        let mut reader = Cursor::new(bytes);
+        let doc = ParsedDocument::from_reader(&mut reader).unwrap();
+        assert_eq!(doc.to_vec().unwrap(), raw.as_bytes());
+        assert_eq!(reader.position(), raw.as_bytes().len() as u64);
+    }
+
+    #[test]
+    fn from_reader_invalid() {
+        let raw = rawdoc! { "a": 1 };
+        let bytes = raw.as_bytes();
+        assert!(matches!(
+            ParsedDocument::from_reader(&bytes[..bytes.len() - 1]),
+            Err(ReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        assert!(matches!(
+            ParsedDocument::from_reader(&bytes[..2]),
+            Err(ReadError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        assert!(matches!(
+            ParsedDocument::from_reader(&[4u8, 0, 0, 0][..]),
+            Err(ReadError::InvalidLength(4))
+        ));
+        let mut corrupt = bytes.to_vec();
+        corrupt[4] = 0x55;
+        assert!(matches!(
+            ParsedDocument::from_reader(corrupt.as_slice()),
+            Err(ReadError::Raw(_))
+        ));
+    }
+
+    #[test]
+    fn stream_reader() {
+        let docs = [rawdoc! { "a": 1 }, rawdoc! {}, rawdoc! { "b": "c" }];
+        let bytes = docs
+            .iter()
+            .flat_map(|d| d.as_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let read = BsonStreamReader::new(bytes.as_slice())
+            .map(|d| d.unwrap().to_vec().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            read,
+            docs.iter()
+                .map(|d| d.as_bytes().to_vec())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(BsonStreamReader::new(&[][..]).count(), 0);
+    }
+
+    #[test]
+    fn stream_reader_truncated() {
+        let raw = rawdoc! { "a": 1 };
+        let mut bytes = raw.as_bytes().to_vec();
+        bytes.extend_from_slice(&raw.as_bytes()[..3]);
+        let mut reader = BsonStreamReader::new(bytes.as_slice());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(reader.next(), Some(Err(ReadError::Io(_)))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn read_back_writer() {
+        let raw = rawdoc! { "borrowed": true };
+        let docs = (0..100i32)
+            .map(|i| {
+                if i % 2 == 0 {
+                    MutableDocument::from(raw.as_ref())
+                } else {
+                    MutableDocument::from(
+                        parsed_doc! { "i": i, "s": "x".repeat(i as usize), "a": [i] },
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer = BsonStreamWriter::new(vec![]);
+        for doc in docs.iter() {
+            writer.write_document(doc).unwrap();
+        }
+        writer.flush().unwrap();
+        let bytes = writer.into_inner();
+
+        let read = BsonStreamReader::new(bytes.as_slice())
+            .map(|d| MutableDocument::from(d.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(read, docs);
+    }
+}
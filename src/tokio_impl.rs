@@ -0,0 +1,109 @@
+use std::io;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    MutableArray, MutableDocument, MutableValue, ParsedDocument, SerializeOptions, encode_to_vec,
+    write::ser_to_io_error,
+};
+
+/// Encode len bytes using put into a buffer and write it to writer.
+///
+/// The length of a document precedes its contents so the value is encoded before writing.
+async fn write_encoded_async<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    len: usize,
+    put: impl FnOnce(&mut Vec<u8>) -> Result<(), bson::ser::Error>,
+) -> io::Result<()> {
+    let buf = encode_to_vec(len, &SerializeOptions::default(), put).map_err(ser_to_io_error)?;
+    writer.write_all(&buf).await
+}
+
+impl MutableValue<'_> {
+    /// Write the encoded value, without a type byte or key, to an async writer.
+    ///
+    /// Fails if the value exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded_async(writer, self.raw_len(), |buf| self.put(buf)).await
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Write the encoded document to an async writer.
+    ///
+    /// Fails if the document exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded_async(writer, self.raw_len(), |buf| self.put(buf)).await
+    }
+}
+
+impl ParsedDocument<'_> {
+    /// Write the encoded document to an async writer.
+    ///
+    /// Fails if the document exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded_async(writer, self.raw_len(), |buf| self.put(buf)).await
+    }
+}
+
+impl MutableArray<'_> {
+    /// Write the encoded array to an async writer.
+    ///
+    /// Fails if the array exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded_async(writer, self.raw_len(), |buf| self.put(buf)).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io;
+
+    use bson::rawdoc;
+
+    use crate::{MutableArray, MutableDocument, MutableValue, ParsedDocument};
+
+    #[tokio::test]
+    async fn write_to_async() {
+        let raw = rawdoc! { "a": "x", "b": [1, { "c": "d" }] };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        doc.insert("e", 2);
+
+        let mut out = vec![];
+        doc.write_to_async(&mut out).await.unwrap();
+        assert_eq!(out, doc.to_vec().unwrap());
+
+        let mut out = vec![];
+        MutableDocument::from(raw.as_ref())
+            .write_to_async(&mut out)
+            .await
+            .unwrap();
+        assert_eq!(out, raw.as_bytes());
+
+        let array = doc.get_array("b").unwrap();
+        let mut out = vec![];
+        array.write_to_async(&mut out).await.unwrap();
+        assert_eq!(out, array.to_vec().unwrap());
+
+        let mut out = vec![];
+        MutableValue::Array(MutableArray::from(vec![MutableValue::from(1)]))
+            .write_to_async(&mut out)
+            .await
+            .unwrap();
+        assert_eq!(out, rawdoc! { "0": 1 }.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn write_to_async_invalid() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a\0", 1);
+        assert_eq!(
+            doc.write_to_async(&mut vec![]).await.unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use crate::{MutableDocument, MutableValue, ParsedDocument};
+
+/// A mutation recorded by a [`TrackedDocument`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// A value was inserted for a key that was not present.
+    Insert(String, MutableValue<'static>),
+    /// The value for an existing key was replaced.
+    Replace(String, MutableValue<'static>),
+    /// An existing key was removed.
+    Remove(String),
+}
+
+impl Change {
+    /// Returns the key this change applies to.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Insert(k, _) | Self::Replace(k, _) | Self::Remove(k) => k,
+        }
+    }
+}
+
+/// A [`MutableDocument`] that records each top-level mutation made through it.
+///
+/// Create one with [`MutableDocument::with_change_log()`]. Mutations made to values obtained from
+/// [`Self::document()`] or after [`Self::into_inner()`] are not recorded.
+#[derive(Clone, Debug)]
+pub struct TrackedDocument<'a> {
+    doc: MutableDocument<'a>,
+    changes: Vec<Change>,
+}
+
+impl<'a> MutableDocument<'a> {
+    /// Wrap this document in a [`TrackedDocument`] with an empty change log.
+    pub fn with_change_log(self) -> TrackedDocument<'a> {
+        TrackedDocument {
+            doc: self,
+            changes: vec![],
+        }
+    }
+}
+
+impl<'a> TrackedDocument<'a> {
+    /// Returns the tracked document.
+    pub fn document(&self) -> &MutableDocument<'a> {
+        &self.doc
+    }
+
+    /// Returns the tracked document, discarding the change log.
+    pub fn into_inner(self) -> MutableDocument<'a> {
+        self.doc
+    }
+
+    /// Returns all changes in the order they were made.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Insert a value for key and return the previous value if any, recording an insert or replace.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn insert<V: Into<MutableValue<'static>>>(
+        &mut self,
+        key: impl Into<String>,
+        value: V,
+    ) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        let key = key.into();
+        let value = value.into();
+        let old = self.doc.insert(key.clone(), value.clone())?;
+        self.changes.push(match old {
+            Some(_) => Change::Replace(key, value),
+            None => Change::Insert(key, value),
+        });
+        Ok(old)
+    }
+
+    /// Replace the value for key if present and return the previous value, recording a replace.
+    /// Does nothing if key is not present.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn replace<V: Into<MutableValue<'static>>>(
+        &mut self,
+        key: impl AsRef<str>,
+        value: V,
+    ) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        let key = key.as_ref();
+        match self.doc.get_mut(key)? {
+            Some(v) => {
+                let value = value.into();
+                let old = std::mem::replace(v, value.clone());
+                self.changes.push(Change::Replace(key.to_string(), value));
+                Ok(Some(old))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Remove key and return the value if present, recording a remove.
+    ///
+    /// May fail with a raw BSON parsing error.
+    pub fn remove(
+        &mut self,
+        key: impl AsRef<str>,
+    ) -> Result<Option<MutableValue<'a>>, bson::raw::Error> {
+        let key = key.as_ref();
+        let old = self.doc.remove(key)?;
+        if old.is_some() {
+            self.changes.push(Change::Remove(key.to_string()));
+        }
+        Ok(old)
+    }
+
+    /// Compress the change log into an update document with `$set` and `$unset` operators that
+    /// transforms the original document into the current one when applied by MongoDB.
+    ///
+    /// Only the last change to each key is kept, and keys that were inserted and later removed
+    /// are omitted. Operators with no changes are omitted, so an empty log produces an empty
+    /// update.
+    pub fn to_update_document(&self) -> ParsedDocument<'static> {
+        // For each key, whether it existed in the original document and its final change.
+        let mut last: HashMap<&str, (bool, &Change)> = HashMap::new();
+        let mut order = vec![];
+        for change in &self.changes {
+            last.entry(change.key())
+                .and_modify(|(_, c)| *c = change)
+                .or_insert_with(|| {
+                    order.push(change.key());
+                    (!matches!(change, Change::Insert(..)), change)
+                });
+        }
+
+        let mut set = ParsedDocument::new();
+        let mut unset = ParsedDocument::new();
+        for key in order {
+            match last[key] {
+                (_, Change::Insert(k, v) | Change::Replace(k, v)) => {
+                    set.insert(k.as_str(), v.clone());
+                }
+                (true, Change::Remove(k)) => {
+                    unset.insert(k.as_str(), "");
+                }
+                (false, Change::Remove(_)) => {}
+            }
+        }
+
+        let mut update = ParsedDocument::new();
+        if !set.is_empty() {
+            update.insert("$set", set);
+        }
+        if !unset.is_empty() {
+            update.insert("$unset", unset);
+        }
+        update
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::{doc, rawdoc};
+
+    use crate::{MutableDocument, MutableValue};
+
+    use super::Change;
+
+    #[test]
+    fn changes() {
+        let raw = rawdoc! { "a": 1, "b": 2 };
+        let mut doc = MutableDocument::from(raw.as_ref()).with_change_log();
+        assert_eq!(doc.insert("c", 3).unwrap(), None);
+        assert_eq!(doc.insert("a", 4).unwrap(), Some(MutableValue::Int32(1)));
+        assert_eq!(doc.replace("b", 5).unwrap(), Some(MutableValue::Int32(2)));
+        assert_eq!(doc.replace("z", 5).unwrap(), None);
+        assert_eq!(doc.remove("c").unwrap(), Some(MutableValue::Int32(3)));
+        assert_eq!(doc.remove("z").unwrap(), None);
+        assert_eq!(
+            doc.changes(),
+            &[
+                Change::Insert("c".into(), MutableValue::Int32(3)),
+                Change::Replace("a".into(), MutableValue::Int32(4)),
+                Change::Replace("b".into(), MutableValue::Int32(5)),
+                Change::Remove("c".into()),
+            ]
+        );
+        assert_eq!(
+            doc.into_inner().try_into_document().unwrap(),
+            doc! { "a": 4, "b": 5 }
+        );
+    }
+
+    #[test]
+    fn to_update_document() {
+        let raw = rawdoc! { "a": 1, "b": 2, "c": 3 };
+        let mut doc = MutableDocument::from(raw.as_ref()).with_change_log();
+        assert!(doc.to_update_document().is_empty());
+
+        doc.insert("a", 4).unwrap();
+        doc.remove("b").unwrap();
+        doc.insert("d", 5).unwrap();
+        doc.remove("d").unwrap();
+        doc.remove("c").unwrap();
+        doc.insert("c", 6).unwrap();
+        doc.insert("a", 7).unwrap();
+        assert_eq!(
+            doc.to_update_document().try_into_document().unwrap(),
+            doc! { "$set": { "a": 7, "c": 6 }, "$unset": { "b": "" } }
+        );
+    }
+}
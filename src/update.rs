@@ -0,0 +1,238 @@
+use crate::{MutableDocument, MutableValue, ParsedDocument, PathError};
+
+/// Errors that may occur when applying an update document.
+#[derive(Clone, Debug)]
+pub enum UpdateError {
+    /// The update document or one of its operands could not be parsed.
+    Raw(bson::raw::Error),
+    /// The update contains a top-level operator other than `$set` or `$unset`.
+    UnsupportedOperator(String),
+    /// The operand of an operator is not a document.
+    InvalidOperand(String),
+    /// An operator path could not be applied to the document.
+    Path(PathError),
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw(e) => write!(f, "{}", e),
+            Self::UnsupportedOperator(op) => write!(f, "unsupported update operator {}", op),
+            Self::InvalidOperand(op) => write!(f, "operand of {} is not a document", op),
+            Self::Path(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<bson::raw::Error> for UpdateError {
+    fn from(value: bson::raw::Error) -> Self {
+        Self::Raw(value)
+    }
+}
+
+impl From<PathError> for UpdateError {
+    fn from(value: PathError) -> Self {
+        Self::Path(value)
+    }
+}
+
+/// Produce an update document with `$set` and `$unset` operators that transforms `old` into `new`
+/// when applied by MongoDB.
+///
+/// Changes within embedded documents are expressed with dot-notation paths. Arrays are treated
+/// atomically: any change to an array sets the entire array. Operators with no changes are
+/// omitted, so identical documents produce an empty update.
+///
+/// May fail with a raw BSON parsing error.
+pub fn diff(
+    old: &ParsedDocument<'_>,
+    new: &ParsedDocument<'_>,
+) -> Result<ParsedDocument<'static>, bson::raw::Error> {
+    let mut set = ParsedDocument::new();
+    let mut unset = ParsedDocument::new();
+    diff_into("", old, new, &mut set, &mut unset)?;
+
+    let mut update = ParsedDocument::new();
+    if !set.is_empty() {
+        update.insert("$set", set);
+    }
+    if !unset.is_empty() {
+        update.insert("$unset", unset);
+    }
+    Ok(update)
+}
+
+fn diff_into(
+    prefix: &str,
+    old: &ParsedDocument<'_>,
+    new: &ParsedDocument<'_>,
+    set: &mut ParsedDocument<'static>,
+    unset: &mut ParsedDocument<'static>,
+) -> Result<(), bson::raw::Error> {
+    for key in old.keys().filter(|k| !new.contains_key(k)) {
+        unset.insert(format!("{}{}", prefix, key), "");
+    }
+
+    for (key, new_value) in new.iter() {
+        let path = format!("{}{}", prefix, key);
+        match (old.get(key), new_value) {
+            (Some(MutableValue::Document(old_doc)), MutableValue::Document(new_doc)) => {
+                let old_doc = old_doc.as_parsed()?;
+                let new_doc = new_doc.as_parsed()?;
+                diff_into(&format!("{}.", path), &old_doc, &new_doc, set, unset)?;
+            }
+            (Some(old_value), new_value) if old_value == new_value => {}
+            _ => {
+                set.insert(path, new_value.clone().into_owned()?);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl ParsedDocument<'_> {
+    /// Apply an update document containing `$set` and `$unset` operators, like one produced by
+    /// [`diff()`].
+    ///
+    /// Operator keys are dot-notation paths applied with [`ParsedDocument::set_path()`] and
+    /// [`ParsedDocument::remove_path()`]. Operators are applied in the order they appear in
+    /// update and the document may be partially updated if an error occurs.
+    pub fn apply_update(&mut self, update: &ParsedDocument<'_>) -> Result<(), UpdateError> {
+        for (op, operand) in update.iter() {
+            let operand = match operand {
+                MutableValue::Document(d) => d.as_parsed()?,
+                _ => return Err(UpdateError::InvalidOperand(op.to_string())),
+            };
+            match op {
+                "$set" => {
+                    for (path, value) in operand.iter() {
+                        self.set_path(path, value.clone().into_owned()?)?;
+                    }
+                }
+                "$unset" => {
+                    for path in operand.keys() {
+                        self.remove_path(path)?;
+                    }
+                }
+                _ => return Err(UpdateError::UnsupportedOperator(op.to_string())),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Apply an update document containing `$set` and `$unset` operators.
+    ///
+    /// See [`ParsedDocument::apply_update()`]; this document is parsed if necessary.
+    pub fn apply_update(&mut self, update: &ParsedDocument<'_>) -> Result<(), UpdateError> {
+        self.to_parsed()?.apply_update(update)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableDocument, ParsedDocument, diff};
+
+    use super::UpdateError;
+
+    fn doc_to_vec(doc: &ParsedDocument<'_>) -> Vec<u8> {
+        doc.to_vec().unwrap()
+    }
+
+    #[test]
+    fn diff_identical() {
+        let raw_doc = rawdoc! { "a": { "b": [1, 2] }, "c": "d" };
+        let doc = ParsedDocument::try_from(raw_doc.as_ref()).unwrap();
+        assert!(diff(&doc, &doc.clone()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diff_nested() {
+        let old_raw = rawdoc! {
+            "a": { "b": 1, "c": 2, "d": { "e": true } },
+            "f": [1, 2, 3],
+            "g": "removed",
+            "h": { "i": 1 },
+        };
+        let old = ParsedDocument::try_from(old_raw.as_ref()).unwrap();
+        let new = parsed_doc! {
+            "a": { "b": 1, "c": 3, "d": { "e": true, "x": 1 } },
+            "f": [1, 2, 4],
+            "h": 5,
+            "j": { "k": "new" },
+        };
+        assert_eq!(
+            doc_to_vec(&diff(&old, &new).unwrap()),
+            rawdoc! {
+                "$set": {
+                    "a.c": 3,
+                    "a.d.x": 1,
+                    "f": [1, 2, 4],
+                    "h": 5,
+                    "j": { "k": "new" },
+                },
+                "$unset": { "g": "" },
+            }
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn apply_update() {
+        let raw_doc = rawdoc! { "a": { "b": 1, "c": [1, 2] }, "d": "e" };
+        let mut doc = MutableDocument::from(raw_doc.as_ref());
+        let update = parsed_doc! {
+            "$set": { "a.b": 2, "a.c.2": 3, "f.g": true },
+            "$unset": { "d": "", "missing": "" },
+        };
+        doc.apply_update(&update).unwrap();
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": { "b": 2, "c": [1, 2, 3] }, "f": { "g": true } }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn apply_update_raw_operand() {
+        let mut doc = parsed_doc! { "a": 1 };
+        let raw_update = rawdoc! { "$set": { "a": { "b": "c" } } };
+        let update = ParsedDocument::try_from(raw_update.as_ref()).unwrap();
+        doc.apply_update(&update).unwrap();
+        assert_eq!(doc_to_vec(&doc), rawdoc! { "a": { "b": "c" } }.as_bytes());
+    }
+
+    #[test]
+    fn apply_update_invalid() {
+        let mut doc = parsed_doc! { "a": 1 };
+        assert!(matches!(
+            doc.apply_update(&parsed_doc! { "$inc": { "a": 1 } }),
+            Err(UpdateError::UnsupportedOperator(op)) if op == "$inc"
+        ));
+        assert!(matches!(
+            doc.apply_update(&parsed_doc! { "$set": 1 }),
+            Err(UpdateError::InvalidOperand(op)) if op == "$set"
+        ));
+        assert!(matches!(
+            doc.apply_update(&parsed_doc! { "$set": { "a.b": 1 } }),
+            Err(UpdateError::Path(_))
+        ));
+    }
+
+    #[test]
+    fn diff_apply_round_trip() {
+        let old_raw = rawdoc! { "a": { "b": 1, "c": { "d": 2 } }, "e": [1], "f": 0 };
+        let old = ParsedDocument::try_from(old_raw.as_ref()).unwrap();
+        let new = parsed_doc! { "a": { "b": 1, "c": { "x": 3 } }, "e": [2], "g": "h" };
+        let mut updated = old.clone();
+        updated.apply_update(&diff(&old, &new).unwrap()).unwrap();
+        assert_eq!(
+            updated.try_into_document().unwrap(),
+            new.try_into_document().unwrap()
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use bson::{Binary, spec::BinarySubtype};
+use uuid::Uuid;
+
+use crate::MutableBinary;
+
+impl MutableBinary<'_> {
+    /// Create a binary value with the legacy UUID subtype (3).
+    ///
+    /// The bytes are in the mixed-endian layout used by the legacy C# driver, where the first
+    /// three fields of the UUID are stored little-endian.
+    pub fn from_uuid_legacy(uuid: Uuid) -> Self {
+        Self::Owned(Binary {
+            subtype: BinarySubtype::UuidOld,
+            bytes: uuid.to_bytes_le().to_vec(),
+        })
+    }
+
+    /// Create a binary value with the standard UUID subtype (4) in big-endian byte order.
+    pub fn from_uuid(uuid: Uuid) -> Self {
+        Self::Owned(Binary {
+            subtype: BinarySubtype::Uuid,
+            bytes: uuid.as_bytes().to_vec(),
+        })
+    }
+
+    /// Returns the value as a UUID if it has the legacy (3) or standard (4) UUID subtype and
+    /// contains 16 bytes.
+    ///
+    /// Legacy UUIDs are decoded using the same mixed-endian layout as [`Self::from_uuid_legacy()`].
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        let bytes: [u8; 16] = self.bytes().try_into().ok()?;
+        match self.subtype() {
+            BinarySubtype::UuidOld => Some(Uuid::from_bytes_le(bytes)),
+            BinarySubtype::Uuid => Some(Uuid::from_bytes(bytes)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bson::spec::BinarySubtype;
+    use uuid::Uuid;
+
+    use crate::MutableBinary;
+
+    const UUID: Uuid = Uuid::from_u128(0x00112233_4455_6677_8899_aabbccddeeff);
+
+    #[test]
+    fn uuid() {
+        let binary = MutableBinary::from_uuid(UUID);
+        assert_eq!(binary.subtype(), BinarySubtype::Uuid);
+        assert_eq!(binary.bytes(), UUID.as_bytes());
+        assert_eq!(binary.as_uuid(), Some(UUID));
+    }
+
+    #[test]
+    fn uuid_legacy() {
+        let binary = MutableBinary::from_uuid_legacy(UUID);
+        assert_eq!(binary.subtype(), BinarySubtype::UuidOld);
+        assert_eq!(
+            binary.bytes(),
+            &[
+                0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+                0xee, 0xff
+            ]
+        );
+        assert_eq!(binary.as_uuid(), Some(UUID));
+    }
+
+    #[test]
+    fn not_uuid() {
+        let mut binary = MutableBinary::from_uuid(UUID);
+        binary.set_subtype(BinarySubtype::Generic);
+        assert_eq!(binary.as_uuid(), None);
+        binary.set_subtype(BinarySubtype::Uuid);
+        binary.set_bytes(vec![0u8; 15]);
+        assert_eq!(binary.as_uuid(), None);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{MutableValue, ParsedDocument};
+
+/// A depth-first visitor over a [`MutableValue`] tree, driven by [`MutableValue::walk()`].
+///
+/// The default implementations of [`Visitor::visit_document()`] and [`Visitor::visit_array()`]
+/// walk each child value, so implementations that override them should walk the children to
+/// continue the traversal.
+pub trait Visitor {
+    /// Error type returned by the visitor. Raw BSON parsing errors may also occur when walking an
+    /// encoded document or array.
+    type Error: From<bson::raw::Error>;
+
+    /// Visit a document. Overrides must walk the values to visit nested values.
+    fn visit_document(&mut self, doc: &ParsedDocument<'_>) -> Result<(), Self::Error> {
+        for value in doc.values() {
+            value.walk(self)?;
+        }
+        Ok(())
+    }
+
+    /// Visit an array. Overrides must walk the elements to visit nested values.
+    fn visit_array(&mut self, values: &[MutableValue<'_>]) -> Result<(), Self::Error> {
+        for value in values {
+            value.walk(self)?;
+        }
+        Ok(())
+    }
+
+    /// Visit any value that is not a document or array.
+    fn visit_value(&mut self, _value: &MutableValue<'_>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A depth-first visitor that may mutate a [`MutableValue`] tree, driven by
+/// [`MutableValue::walk_mut()`].
+///
+/// Documents and arrays are parsed before they are visited. The default implementations of
+/// [`VisitorMut::visit_document()`] and [`VisitorMut::visit_array()`] walk each child value.
+pub trait VisitorMut {
+    /// Error type returned by the visitor. Raw BSON parsing errors may also occur when parsing an
+    /// encoded document or array.
+    type Error: From<bson::raw::Error>;
+
+    /// Visit a parsed document. Overrides must walk the values to visit nested values.
+    fn visit_document(&mut self, doc: &mut ParsedDocument<'_>) -> Result<(), Self::Error> {
+        for value in doc.values_mut() {
+            value.walk_mut(self)?;
+        }
+        Ok(())
+    }
+
+    /// Visit a parsed array. Overrides must walk the elements to visit nested values.
+    fn visit_array(&mut self, values: &mut Vec<MutableValue<'_>>) -> Result<(), Self::Error> {
+        for value in values.iter_mut() {
+            value.walk_mut(self)?;
+        }
+        Ok(())
+    }
+
+    /// Visit any value that is not a document or array.
+    fn visit_value(&mut self, _value: &mut MutableValue<'_>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl MutableValue<'_> {
+    /// Walk this value depth-first with visitor.
+    ///
+    /// Encoded documents and arrays are parsed to visit them without modifying this value, which
+    /// may fail with a raw BSON parsing error.
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) -> Result<(), V::Error> {
+        match self {
+            Self::Document(d) => visitor.visit_document(d.as_parsed()?.as_ref()),
+            Self::Array(a) => visitor.visit_array(a.as_parsed()?.as_ref()),
+            v => visitor.visit_value(v),
+        }
+    }
+
+    /// Walk this value depth-first with visitor, allowing it to mutate values in place.
+    ///
+    /// Any encoded documents or arrays that are visited are parsed, which may fail with a raw BSON
+    /// parsing error.
+    pub fn walk_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        match self {
+            Self::Document(d) => visitor.visit_document(d.to_parsed()?),
+            Self::Array(a) => visitor.visit_array(a.to_parsed()?),
+            v => visitor.visit_value(v),
+        }
+    }
+}
+
+impl<'a> ParsedDocument<'a> {
+    /// Call f with the dot-notation path and a mutable reference to every value in the document
+    /// tree that is not a document or array, in document order.
+    ///
+    /// Any encoded documents or arrays are parsed, which may fail with a raw BSON parsing error.
+    pub fn iter_mut_recursive<F: FnMut(&str, &mut MutableValue<'a>)>(
+        &mut self,
+        mut f: F,
+    ) -> Result<(), bson::raw::Error> {
+        let mut path = String::new();
+        for (k, v) in self.iter_mut() {
+            path.push_str(k);
+            visit_leaves(v, &mut path, &mut f)?;
+            path.clear();
+        }
+        Ok(())
+    }
+}
+
+fn visit_leaves<'a, F: FnMut(&str, &mut MutableValue<'a>)>(
+    value: &mut MutableValue<'a>,
+    path: &mut String,
+    f: &mut F,
+) -> Result<(), bson::raw::Error> {
+    let visit_child = |path: &mut String, key: &str, child, f: &mut F| {
+        let len = path.len();
+        path.push('.');
+        path.push_str(key);
+        let result = visit_leaves(child, path, f);
+        path.truncate(len);
+        result
+    };
+    match value {
+        MutableValue::Document(d) => {
+            for (k, v) in d.to_parsed()?.iter_mut() {
+                visit_child(path, k, v, f)?;
+            }
+        }
+        MutableValue::Array(a) => {
+            for (i, v) in a.to_parsed()?.iter_mut().enumerate() {
+                visit_child(path, &i.to_string(), v, f)?;
+            }
+        }
+        v => f(path, v),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use bson::rawdoc;
+
+    use crate::{MutableValue, ParsedDocument};
+
+    use super::{Visitor, VisitorMut};
+
+    #[derive(Default)]
+    struct CountVisitor {
+        documents: usize,
+        arrays: usize,
+        values: usize,
+    }
+
+    impl Visitor for CountVisitor {
+        type Error = bson::raw::Error;
+
+        fn visit_document(&mut self, doc: &ParsedDocument<'_>) -> Result<(), Self::Error> {
+            self.documents += 1;
+            for value in doc.values() {
+                value.walk(self)?;
+            }
+            Ok(())
+        }
+
+        fn visit_array(&mut self, values: &[MutableValue<'_>]) -> Result<(), Self::Error> {
+            self.arrays += 1;
+            for value in values {
+                value.walk(self)?;
+            }
+            Ok(())
+        }
+
+        fn visit_value(&mut self, _value: &MutableValue<'_>) -> Result<(), Self::Error> {
+            self.values += 1;
+            Ok(())
+        }
+    }
+
+    struct WidenVisitor;
+
+    impl VisitorMut for WidenVisitor {
+        type Error = bson::raw::Error;
+
+        fn visit_value(&mut self, value: &mut MutableValue<'_>) -> Result<(), Self::Error> {
+            if let MutableValue::Int32(v) = value {
+                *value = MutableValue::Int64((*v).into());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let raw = rawdoc! { "a": 1, "b": { "c": [2, { "d": "e" }], "f": [] } };
+        let value = MutableValue::Document(raw.as_ref().into());
+        let mut visitor = CountVisitor::default();
+        value.walk(&mut visitor).unwrap();
+        assert_eq!(
+            (visitor.documents, visitor.arrays, visitor.values),
+            (3, 2, 3)
+        );
+    }
+
+    #[test]
+    fn walk_mut() {
+        let raw = rawdoc! { "a": 1, "b": { "c": [2, { "d": "e" }] } };
+        let mut value = MutableValue::Document(raw.as_ref().into());
+        value.walk_mut(&mut WidenVisitor).unwrap();
+        assert_eq!(
+            value.as_doc().unwrap().to_vec().unwrap(),
+            rawdoc! { "a": 1i64, "b": { "c": [2i64, { "d": "e" }] } }.as_bytes()
+        );
+    }
+
+    #[test]
+    fn iter_mut_recursive() {
+        let raw = rawdoc! { "a": 1, "b": { "c": "x", "d": [2, { "e": 3 }] }, "f": {} };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        let mut paths = vec![];
+        doc.iter_mut_recursive(|path, value| {
+            paths.push(path.to_string());
+            if let Some(v) = value.as_i32() {
+                *value = MutableValue::from(v * 10);
+            }
+        })
+        .unwrap();
+        assert_eq!(paths, vec!["a", "b.c", "b.d.0", "b.d.1.e"]);
+        assert_eq!(
+            doc.to_vec().unwrap(),
+            rawdoc! { "a": 10, "b": { "c": "x", "d": [20, { "e": 30 }] }, "f": {} }.as_bytes()
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
+
+use bytes::{BufMut, buf::UninitSlice};
+
+use crate::{
+    MutableArray, MutableDocument, MutableValue, ParsedDocument, SerializeOptions,
+    check_encoded_len,
+};
+
+const WRITE_BUF_LEN: usize = 8192;
+
+/// Adapts a [`Write`] to [`BufMut`] by buffering small writes and passing large slices through.
+///
+/// `BufMut` methods cannot fail, so the first write error is retained and returned by
+/// [`Self::finish()`]; all output after an error is discarded.
+struct WriteBuf<W> {
+    writer: W,
+    buf: Vec<u8>,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> WriteBuf<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::with_capacity(WRITE_BUF_LEN),
+            error: None,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none()
+            && let Err(e) = self.writer.write_all(bytes)
+        {
+            self.error = Some(e);
+        }
+    }
+
+    fn flush_buf(&mut self) {
+        let buf = std::mem::take(&mut self.buf);
+        self.write(&buf);
+        self.buf = buf;
+        self.buf.clear();
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.flush_buf();
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+unsafe impl<W: Write> BufMut for WriteBuf<W> {
+    fn remaining_mut(&self) -> usize {
+        isize::MAX as usize
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        // SAFETY: the caller has initialized cnt bytes of the chunk returned by chunk_mut().
+        unsafe { self.buf.advance_mut(cnt) };
+        if self.buf.len() >= WRITE_BUF_LEN {
+            self.flush_buf();
+        }
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        self.buf.chunk_mut()
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        if self.buf.len() + src.len() > WRITE_BUF_LEN {
+            self.flush_buf();
+            if src.len() >= WRITE_BUF_LEN {
+                self.write(src);
+                return;
+            }
+        }
+        self.buf.extend_from_slice(src);
+    }
+}
+
+/// Check the encoded length and write the output of put to writer.
+fn write_encoded<W: Write>(
+    writer: &mut W,
+    len: usize,
+    put: impl FnOnce(&mut WriteBuf<&mut W>) -> Result<(), bson::ser::Error>,
+) -> io::Result<()> {
+    check_encoded_len(len, &SerializeOptions::default()).map_err(ser_to_io_error)?;
+    let mut buf = WriteBuf::new(writer);
+    put(&mut buf).map_err(ser_to_io_error)?;
+    buf.finish()
+}
+
+pub(crate) fn ser_to_io_error(e: bson::ser::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Wrap an error that prevents a value from being encoded in a serialization error.
+pub(crate) fn invalid_data_ser_error(
+    e: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> bson::ser::Error {
+    bson::ser::Error::Io(Arc::new(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+impl MutableValue<'_> {
+    /// Write the encoded value, without a type byte or key, to writer.
+    ///
+    /// Fails if the value exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded(writer, self.raw_len(), |buf| self.put(buf))
+    }
+}
+
+impl MutableDocument<'_> {
+    /// Write the encoded document to writer without allocating the whole document in memory.
+    ///
+    /// Fails if the document exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded(writer, self.raw_len(), |buf| self.put(buf))
+    }
+}
+
+impl ParsedDocument<'_> {
+    /// Write the encoded document to writer without allocating the whole document in memory.
+    ///
+    /// Fails if the document exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded(writer, self.raw_len(), |buf| self.put(buf))
+    }
+}
+
+impl MutableArray<'_> {
+    /// Write the encoded array to writer without allocating the whole array in memory.
+    ///
+    /// Fails if the array exceeds the default maximum document size, cannot be encoded, or if
+    /// writer fails.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_encoded(writer, self.raw_len(), |buf| self.put(buf))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Write};
+
+    use bson::rawdoc;
+
+    use crate::{MutableArray, MutableDocument, MutableValue, ParsedDocument};
+
+    /// Accepts limit bytes and then fails.
+    struct LimitedWriter {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for LimitedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.limit - self.written.len());
+            if n == 0 && !buf.is_empty() {
+                return Err(io::Error::other("limit exceeded"));
+            }
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_to() {
+        let raw = rawdoc! { "a": "x".repeat(10_000), "b": [1, { "c": "d" }] };
+        let mut doc = ParsedDocument::try_from(raw.as_ref()).unwrap();
+        doc.insert("e", "y".repeat(100));
+        doc.get_array_mut("b").unwrap().push(2).unwrap();
+
+        let mut out = vec![];
+        doc.write_to(&mut out).unwrap();
+        assert_eq!(out, doc.to_vec().unwrap());
+
+        let mut out = vec![];
+        MutableDocument::from(raw.as_ref())
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, raw.as_bytes());
+
+        let array = doc.get_array("b").unwrap();
+        let mut out = vec![];
+        array.write_to(&mut out).unwrap();
+        assert_eq!(out, array.to_vec().unwrap());
+
+        let mut out = vec![];
+        MutableValue::Array(MutableArray::from(vec![MutableValue::from(1)]))
+            .write_to(&mut out)
+            .unwrap();
+        assert_eq!(out, rawdoc! { "0": 1 }.as_bytes());
+    }
+
+    #[test]
+    fn write_to_errors() {
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", "x".repeat(10_000));
+        let mut writer = LimitedWriter {
+            written: vec![],
+            limit: 100,
+        };
+        assert!(doc.write_to(&mut writer).is_err());
+
+        let mut doc = ParsedDocument::new();
+        doc.insert("a\0", 1);
+        assert_eq!(
+            doc.write_to(&mut vec![]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut doc = ParsedDocument::new();
+        doc.insert("a", "x".repeat(32 << 20));
+        assert_eq!(
+            doc.write_to(&mut vec![]).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}